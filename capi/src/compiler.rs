@@ -565,6 +565,33 @@ pub unsafe extern "C" fn yrx_compiler_warnings_json(
     }
 }
 
+/// Sets the maximum number of matches that are recorded per pattern.
+///
+/// When a pattern like `$a = "foo"` matches millions of times in the
+/// scanned data, recording every single match can consume a large amount of
+/// memory and time. This function caps how many matches are kept for each
+/// individual pattern; once the cap is reached for a pattern, the scanner
+/// stops recording further matches for it (the rule can still match, only
+/// the match list is truncated), and a "too many matches" warning is added
+/// to the warnings produced by the compilation.
+///
+/// A value of 0 means that there's no limit, which is the default behavior.
+#[no_mangle]
+pub unsafe extern "C" fn yrx_compiler_set_max_matches_per_pattern(
+    compiler: *mut YRX_COMPILER,
+    max_matches: u32,
+) -> YRX_RESULT {
+    let compiler = if let Some(compiler) = compiler.as_mut() {
+        compiler
+    } else {
+        return YRX_RESULT::YRX_INVALID_ARGUMENT;
+    };
+
+    compiler.inner.max_matches_per_pattern(max_matches as usize);
+
+    YRX_RESULT::YRX_SUCCESS
+}
+
 /// Builds the source code previously added to the compiler.
 ///
 /// After calling this function the compiler is reset to its initial state,
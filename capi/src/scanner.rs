@@ -0,0 +1,366 @@
+use std::ffi::{c_char, c_void};
+use std::slice;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::{YRX_PATTERN, YRX_RESULT, YRX_RULE, YRX_RULES};
+
+/// A scanner that scans data with a set of compiled [`YRX_RULES`].
+pub struct YRX_SCANNER<'a> {
+    inner: yara_x::Scanner<'a>,
+    on_event: Option<(YRX_SCAN_EVENT_CALLBACK, SendPtr)>,
+    abort_requested: Arc<AtomicBool>,
+}
+
+// `user_data` is an opaque pointer supplied by the caller. We don't
+// dereference it ourselves, we just hand it back to the callback, which
+// is always invoked from the thread that called `yrx_scanner_scan`.
+struct SendPtr(*mut c_void);
+unsafe impl Send for SendPtr {}
+
+impl<'a> YRX_SCANNER<'a> {
+    pub(crate) fn new(rules: &'a yara_x::Rules) -> Self {
+        Self {
+            inner: yara_x::Scanner::new(rules),
+            on_event: None,
+            abort_requested: Arc::new(AtomicBool::new(false)),
+        }
+    }
+}
+
+/// Events that can be received by the callback passed to
+/// [`yrx_scanner_on_event`].
+#[repr(C)]
+pub enum YRX_SCAN_EVENT {
+    /// Produced right before the scan starts.
+    YRX_SCAN_STARTED = 0,
+    /// Produced after the scan finishes, either because all rules were
+    /// evaluated or because the callback requested an abort.
+    YRX_SCAN_FINISHED = 1,
+    /// Produced when a rule matches. `rule` points to the matching
+    /// [`YRX_RULE`].
+    YRX_RULE_MATCHING = 2,
+    /// Produced when a rule doesn't match. `rule` points to the
+    /// non-matching [`YRX_RULE`].
+    YRX_RULE_NOT_MATCHING = 3,
+    /// Produced when a module is imported by the rules being evaluated.
+    /// `module_name` holds the imported module's name.
+    YRX_MODULE_IMPORTED = 4,
+}
+
+/// Data passed to the callback registered with [`yrx_scanner_on_event`].
+///
+/// The meaning of `rule` and `module_name` depends on `event`: for
+/// [`YRX_SCAN_EVENT::YRX_RULE_MATCHING`] and
+/// [`YRX_SCAN_EVENT::YRX_RULE_NOT_MATCHING`] `rule` points to the
+/// [`YRX_RULE`] the event is about, and `module_name` is null. For
+/// [`YRX_SCAN_EVENT::YRX_MODULE_IMPORTED`] `module_name` points to a
+/// NUL-terminated string with the imported module's name, and `rule` is
+/// null. For any other event both are null.
+///
+/// Both pointers are only valid for the duration of the callback
+/// invocation; don't retain them past that call.
+#[repr(C)]
+pub struct YRX_SCAN_EVENT_DATA {
+    /// The type of event.
+    pub event: YRX_SCAN_EVENT,
+    /// The rule associated to the event, or null.
+    pub rule: *const YRX_RULE,
+    /// The module name associated to a [`YRX_SCAN_EVENT::YRX_MODULE_IMPORTED`]
+    /// event, or null.
+    pub module_name: *const c_char,
+}
+
+/// Value returned by the callback passed to [`yrx_scanner_on_event`] for
+/// letting the scan continue.
+pub const YRX_CONTINUE: i32 = 0;
+
+/// Value returned by the callback passed to [`yrx_scanner_on_event`] for
+/// aborting the scan as soon as possible.
+pub const YRX_ABORT: i32 = 1;
+
+/// Callback function passed to [`yrx_scanner_on_event`].
+///
+/// `user_data` is the same pointer that was passed to
+/// [`yrx_scanner_on_event`], handed back unchanged so that the callback can
+/// recover whatever context it needs. The callback must return either
+/// [`YRX_CONTINUE`] or [`YRX_ABORT`].
+pub type YRX_SCAN_EVENT_CALLBACK = unsafe extern "C" fn(
+    event: *const YRX_SCAN_EVENT_DATA,
+    user_data: *mut c_void,
+) -> i32;
+
+/// Creates a [`YRX_SCANNER`] object that uses the given [`YRX_RULES`] for
+/// scanning data.
+///
+/// It's ok to create multiple [`YRX_SCANNER`] objects that use the same
+/// [`YRX_RULES`] object, even from different threads, as long as the
+/// [`YRX_RULES`] object is not destroyed while the scanners are in use.
+#[no_mangle]
+pub unsafe extern "C" fn yrx_scanner_create<'a>(
+    rules: *const YRX_RULES,
+    scanner: &mut *mut YRX_SCANNER<'a>,
+) -> YRX_RESULT {
+    let rules = if let Some(rules) = rules.as_ref() {
+        rules
+    } else {
+        return YRX_RESULT::YRX_INVALID_ARGUMENT;
+    };
+
+    *scanner = Box::into_raw(Box::new(YRX_SCANNER::new(rules.as_inner())));
+
+    YRX_RESULT::YRX_SUCCESS
+}
+
+/// Destroys a [`YRX_SCANNER`] object.
+#[no_mangle]
+pub unsafe extern "C" fn yrx_scanner_destroy(scanner: *mut YRX_SCANNER) {
+    drop(Box::from_raw(scanner))
+}
+
+/// Sets the maximum number of matches that are recorded per pattern during
+/// a scan, overriding the limit set at compile time with
+/// [`crate::yrx_compiler_set_max_matches_per_pattern`] for the scans
+/// performed with this scanner.
+///
+/// A value of 0 means that there's no limit.
+#[no_mangle]
+pub unsafe extern "C" fn yrx_scanner_set_max_matches_per_pattern(
+    scanner: *mut YRX_SCANNER,
+    max_matches: u32,
+) -> YRX_RESULT {
+    let scanner = if let Some(scanner) = scanner.as_mut() {
+        scanner
+    } else {
+        return YRX_RESULT::YRX_INVALID_ARGUMENT;
+    };
+
+    scanner.inner.max_matches_per_pattern(max_matches as usize);
+
+    YRX_RESULT::YRX_SUCCESS
+}
+
+/// Sets a timeout for each scan performed with this scanner.
+///
+/// After setting a timeout, scans abort once the specified number of
+/// `seconds` have elapsed, and [`yrx_scanner_scan`] returns
+/// `YRX_SCAN_TIMEOUT`.
+#[no_mangle]
+pub unsafe extern "C" fn yrx_scanner_set_timeout(
+    scanner: *mut YRX_SCANNER,
+    seconds: u64,
+) -> YRX_RESULT {
+    let scanner = if let Some(scanner) = scanner.as_mut() {
+        scanner
+    } else {
+        return YRX_RESULT::YRX_INVALID_ARGUMENT;
+    };
+
+    scanner.inner.set_timeout(Duration::from_secs(seconds));
+
+    YRX_RESULT::YRX_SUCCESS
+}
+
+/// Requests that the ongoing scan performed by `scanner` be aborted.
+///
+/// The request only affects a scan that is already running: each call to
+/// [`yrx_scanner_scan`] clears any pending request when it starts.
+///
+/// Unlike the rest of the functions in this module, this one is safe to call
+/// from a thread different than the one running [`yrx_scanner_scan`], which
+/// is precisely its purpose: it lets a supervisor thread cancel a scan that
+/// is taking too long, without waiting for a fixed timeout to expire. The
+/// abort is cooperative: evaluation stops cleanly the next time the scanner
+/// checks for pending cancellation, which happens between rules.
+#[no_mangle]
+pub unsafe extern "C" fn yrx_scanner_request_abort(
+    scanner: *mut YRX_SCANNER,
+) -> YRX_RESULT {
+    let scanner = if let Some(scanner) = scanner.as_ref() {
+        scanner
+    } else {
+        return YRX_RESULT::YRX_INVALID_ARGUMENT;
+    };
+
+    scanner.abort_requested.store(true, Ordering::SeqCst);
+
+    YRX_RESULT::YRX_SUCCESS
+}
+
+/// Registers a callback that is invoked synchronously for every
+/// [`YRX_SCAN_EVENT`] produced while scanning, instead of waiting until the
+/// scan finishes and iterating the results.
+///
+/// If the callback returns [`YRX_ABORT`] the scan stops as soon as possible,
+/// without evaluating the rules that haven't been evaluated yet. This lets
+/// long scans over large buffers stop as soon as the caller has seen enough
+/// (e.g. the first matching rule), without materializing a full results set.
+///
+/// `user_data` can be a pointer to any data the caller wants to receive back
+/// in subsequent calls to the callback. It can be null if not needed.
+#[no_mangle]
+pub unsafe extern "C" fn yrx_scanner_on_event(
+    scanner: *mut YRX_SCANNER,
+    callback: YRX_SCAN_EVENT_CALLBACK,
+    user_data: *mut c_void,
+) -> YRX_RESULT {
+    let scanner = if let Some(scanner) = scanner.as_mut() {
+        scanner
+    } else {
+        return YRX_RESULT::YRX_INVALID_ARGUMENT;
+    };
+
+    scanner.on_event = Some((callback, SendPtr(user_data)));
+
+    YRX_RESULT::YRX_SUCCESS
+}
+
+/// Scans in-memory data with the rules associated to the scanner.
+///
+/// If a callback was registered with [`yrx_scanner_on_event`], it is invoked
+/// for the scan's start and finish, and for every rule as its matching
+/// status is resolved, and the scan is aborted as soon as the callback
+/// returns [`YRX_ABORT`].
+#[no_mangle]
+pub unsafe extern "C" fn yrx_scanner_scan(
+    scanner: *mut YRX_SCANNER,
+    data: *const u8,
+    len: usize,
+) -> YRX_RESULT {
+    let scanner = if let Some(scanner) = scanner.as_mut() {
+        scanner
+    } else {
+        return YRX_RESULT::YRX_INVALID_ARGUMENT;
+    };
+
+    let data = if data.is_null() {
+        &[]
+    } else {
+        slice::from_raw_parts(data, len)
+    };
+
+    let emit = |scanner: &YRX_SCANNER,
+                event: YRX_SCAN_EVENT,
+                rule: *const YRX_RULE,
+                module_name: *const c_char|
+     -> i32 {
+        if let Some((callback, user_data)) = &scanner.on_event {
+            let event_data =
+                YRX_SCAN_EVENT_DATA { event, rule, module_name };
+            callback(&event_data as *const YRX_SCAN_EVENT_DATA, user_data.0)
+        } else {
+            YRX_CONTINUE
+        }
+    };
+
+    scanner.abort_requested.store(false, Ordering::SeqCst);
+
+    if emit(
+        scanner,
+        YRX_SCAN_EVENT::YRX_SCAN_STARTED,
+        std::ptr::null(),
+        std::ptr::null(),
+    ) == YRX_ABORT
+        || scanner.abort_requested.load(Ordering::SeqCst)
+    {
+        emit(
+            scanner,
+            YRX_SCAN_EVENT::YRX_SCAN_FINISHED,
+            std::ptr::null(),
+            std::ptr::null(),
+        );
+        return YRX_RESULT::YRX_SUCCESS;
+    }
+
+    // `scan_with_callback` evaluates rules one at a time and invokes this
+    // closure as soon as each rule's (or module import's) status is known,
+    // instead of running the whole scan to completion first and handing
+    // back a finished `ScanResults` to iterate afterwards. Returning
+    // `yara_x::CallbackResult::Abort` from here — because the caller's own
+    // callback asked for it, or because `yrx_scanner_request_abort` was
+    // called from another thread in the meantime — stops the underlying
+    // rule evaluation right there, which is what actually makes early
+    // abort cheap.
+    let mut aborted = false;
+    let scan_result = scanner.inner.scan_with_callback(data, |event| {
+        if scanner.abort_requested.load(Ordering::SeqCst) {
+            aborted = true;
+            return yara_x::CallbackResult::Abort;
+        }
+
+        // Built up front so its pointer stays valid for the `emit` call
+        // below; `YRX_SCAN_EVENT_DATA` documents that `module_name` is only
+        // valid for the duration of the callback invocation.
+        let module_name_cstring;
+
+        let (kind, rule, module_name) = match event {
+            yara_x::ScanEvent::RuleMatching(rule) => (
+                YRX_SCAN_EVENT::YRX_RULE_MATCHING,
+                Some(YRX_RULE::from(rule)),
+                std::ptr::null(),
+            ),
+            yara_x::ScanEvent::RuleNotMatching(rule) => (
+                YRX_SCAN_EVENT::YRX_RULE_NOT_MATCHING,
+                Some(YRX_RULE::from(rule)),
+                std::ptr::null(),
+            ),
+            yara_x::ScanEvent::ModuleImported(module_name) => {
+                module_name_cstring =
+                    std::ffi::CString::new(module_name).unwrap_or_default();
+                (
+                    YRX_SCAN_EVENT::YRX_MODULE_IMPORTED,
+                    None,
+                    module_name_cstring.as_ptr(),
+                )
+            }
+        };
+
+        let rule_ptr = rule
+            .as_ref()
+            .map(|r| r as *const YRX_RULE)
+            .unwrap_or(std::ptr::null());
+
+        if emit(scanner, kind, rule_ptr, module_name) == YRX_ABORT {
+            aborted = true;
+            yara_x::CallbackResult::Abort
+        } else {
+            yara_x::CallbackResult::Continue
+        }
+    });
+
+    match scan_result {
+        Ok(_) => {}
+        Err(yara_x::errors::ScanError::Timeout) => {
+            return YRX_RESULT::YRX_SCAN_TIMEOUT
+        }
+        Err(_) => return YRX_RESULT::YRX_SCAN_ERROR,
+    };
+
+    emit(
+        scanner,
+        YRX_SCAN_EVENT::YRX_SCAN_FINISHED,
+        std::ptr::null(),
+        std::ptr::null(),
+    );
+
+    if aborted {
+        YRX_RESULT::YRX_SCAN_ABORTED
+    } else {
+        YRX_RESULT::YRX_SUCCESS
+    }
+}
+
+/// Returns true if the list of matches returned by [`crate::yrx_pattern_get_matches`]
+/// for this pattern was truncated because the pattern reached the maximum
+/// number of matches set with [`yrx_compiler_set_max_matches_per_pattern`]
+/// or [`yrx_scanner_set_max_matches_per_pattern`].
+#[no_mangle]
+pub unsafe extern "C" fn yrx_pattern_matches_truncated(
+    pattern: *const YRX_PATTERN,
+) -> bool {
+    match pattern.as_ref() {
+        Some(pattern) => pattern.matches_truncated(),
+        None => false,
+    }
+}
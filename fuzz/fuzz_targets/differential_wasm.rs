@@ -0,0 +1,9 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+// All the logic lives in `yara_x::fuzzing::run`, which needs crate-internal
+// access to `WasmModuleBuilder`. This target is just the libfuzzer shim.
+fuzz_target!(|data: &[u8]| {
+    yara_x::fuzzing::run(data);
+});
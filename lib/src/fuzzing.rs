@@ -0,0 +1,416 @@
+//! Differential fuzzing harness for [`crate::wasm::builder::WasmModuleBuilder`].
+//!
+//! Gated behind the `fuzzing` Cargo feature and wired into the crate root
+//! with `#[cfg(feature = "fuzzing")] pub mod fuzzing;`. The harness lives
+//! here, rather than directly in `fuzz/fuzz_targets/`, because it needs
+//! access to `WasmModuleBuilder`, which is `pub(crate)`;
+//! `fuzz/fuzz_targets/differential_wasm.rs` is a thin `libfuzzer_sys`
+//! wrapper that just calls [`run`].
+//!
+//! The approach: generate a random tree of YARA rules, organized into
+//! namespaces, where each rule's condition is a boolean expression over
+//! "did pattern N match" terms, plus a random bitmap recording which
+//! pattern ids "matched" in this run. Lower that straight into
+//! `WasmModuleBuilder` calls — bypassing the parser and compiler, which
+//! this harness has no access to — while also evaluating the same rule
+//! tree with a small tree-walking reference evaluator that never goes
+//! anywhere near WASM. Compile, validate and instantiate the generated
+//! module with `wasmtime`, run it, and diff the rule-match results it
+//! reports (via the `rule_match`/`rule_no_match` host imports, which this
+//! harness stubs out itself) against the reference evaluator. Any
+//! divergence, trap or validation failure is a bug in the codegen.
+//!
+//! `namespaces_per_func`/`rules_per_func` are driven down to small values
+//! relative to the generated rule/namespace counts, so most runs exercise
+//! at least one function-partition boundary instead of only the common
+//! case where everything fits in a single `rules_0`/`namespaces_0`.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use arbitrary::{Arbitrary, Unstructured};
+use wasmtime::{Engine, Linker, Memory, MemoryType, Module, Store};
+
+use crate::compiler::RuleId;
+use crate::wasm::builder::WasmModuleBuilder;
+
+/// Bounds the depth of a generated [`Condition`] tree, and therefore both
+/// the `Arbitrary` recursion below and the size of the WASM emitted for a
+/// single rule.
+const MAX_CONDITION_DEPTH: u32 = 4;
+
+/// A boolean expression over "did pattern `id` match". Lowered into WASM
+/// instructions by [`emit`] and evaluated directly by [`eval`] — the fact
+/// that both read the same tree is what makes the two paths comparable.
+#[derive(Debug, Clone)]
+enum Condition {
+    PatternMatch(u32),
+    Not(Box<Condition>),
+    And(Box<Condition>, Box<Condition>),
+    Or(Box<Condition>, Box<Condition>),
+}
+
+impl Condition {
+    fn new(
+        u: &mut Unstructured,
+        depth: u32,
+        num_patterns: u32,
+    ) -> arbitrary::Result<Self> {
+        let pattern_id = |u: &mut Unstructured| {
+            u.int_in_range(0..=num_patterns.saturating_sub(1))
+        };
+        if depth >= MAX_CONDITION_DEPTH || u.is_empty() {
+            return Ok(Condition::PatternMatch(pattern_id(u)?));
+        }
+        Ok(match u.int_in_range(0u8..=3)? {
+            0 => Condition::PatternMatch(pattern_id(u)?),
+            1 => Condition::Not(Box::new(Self::new(
+                u,
+                depth + 1,
+                num_patterns,
+            )?)),
+            2 => Condition::And(
+                Box::new(Self::new(u, depth + 1, num_patterns)?),
+                Box::new(Self::new(u, depth + 1, num_patterns)?),
+            ),
+            _ => Condition::Or(
+                Box::new(Self::new(u, depth + 1, num_patterns)?),
+                Box::new(Self::new(u, depth + 1, num_patterns)?),
+            ),
+        })
+    }
+
+    /// The non-WASM reference path.
+    fn eval(&self, matched_patterns: &[bool]) -> bool {
+        match self {
+            Condition::PatternMatch(id) => matched_patterns[*id as usize],
+            Condition::Not(c) => !c.eval(matched_patterns),
+            Condition::And(a, b) => {
+                a.eval(matched_patterns) && b.eval(matched_patterns)
+            }
+            Condition::Or(a, b) => {
+                a.eval(matched_patterns) || b.eval(matched_patterns)
+            }
+        }
+    }
+
+    /// Lowers this condition into `seq`, leaving the i32 `start_rule`
+    /// expects on the stack: 1 if the condition is true, 0 otherwise.
+    fn emit(
+        &self,
+        seq: &mut walrus::InstrSeqBuilder,
+        check_for_pattern_match: walrus::FunctionId,
+    ) {
+        use walrus::ir::{BinaryOp, UnaryOp};
+        match self {
+            Condition::PatternMatch(id) => {
+                seq.i32_const(*id as i32).call(check_for_pattern_match);
+            }
+            Condition::Not(c) => {
+                c.emit(seq, check_for_pattern_match);
+                seq.unop(UnaryOp::I32Eqz);
+            }
+            Condition::And(a, b) => {
+                a.emit(seq, check_for_pattern_match);
+                b.emit(seq, check_for_pattern_match);
+                seq.binop(BinaryOp::I32And);
+            }
+            Condition::Or(a, b) => {
+                a.emit(seq, check_for_pattern_match);
+                b.emit(seq, check_for_pattern_match);
+                seq.binop(BinaryOp::I32Or);
+            }
+        }
+    }
+}
+
+struct FuzzRule {
+    id: u32,
+    global: bool,
+    condition: Condition,
+}
+
+struct FuzzNamespace {
+    rules: Vec<FuzzRule>,
+}
+
+struct FuzzRuleset {
+    namespaces: Vec<FuzzNamespace>,
+    num_patterns: u32,
+    matched_patterns: Vec<bool>,
+    namespaces_per_func: usize,
+    rules_per_func: usize,
+}
+
+impl FuzzRuleset {
+    fn new(u: &mut Unstructured) -> arbitrary::Result<Self> {
+        let num_patterns = u.int_in_range(1..=32u32)?;
+        let matched_patterns = (0..num_patterns)
+            .map(|_| bool::arbitrary(u))
+            .collect::<Result<_, _>>()?;
+
+        let num_namespaces = u.int_in_range(1..=12u32)?;
+        let mut next_rule_id = 0u32;
+        let mut namespaces = Vec::new();
+
+        for _ in 0..num_namespaces {
+            let num_rules = u.int_in_range(1..=12u32)?;
+            let mut rules = Vec::new();
+            for _ in 0..num_rules {
+                rules.push(FuzzRule {
+                    id: next_rule_id,
+                    global: bool::arbitrary(u)?,
+                    condition: Condition::new(u, 0, num_patterns)?,
+                });
+                next_rule_id += 1;
+            }
+            namespaces.push(FuzzNamespace { rules });
+        }
+
+        Ok(FuzzRuleset {
+            namespaces,
+            num_patterns,
+            matched_patterns,
+            // Kept small and independent of the generated rule/namespace
+            // counts on purpose, so that most runs straddle at least one
+            // `rules_N`/`namespaces_N` partition boundary.
+            namespaces_per_func: u.int_in_range(1..=4usize)?,
+            rules_per_func: u.int_in_range(1..=4usize)?,
+        })
+    }
+
+    /// The reference evaluation: a rule whose condition is false reverts
+    /// every rule matched so far in its namespace and stops evaluating
+    /// that namespace if it's global, mirroring the early-exit `br_if`
+    /// that `WasmModuleBuilder::finish_rule`/`build` wire into the
+    /// generated `namespaces_N` function.
+    fn eval_reference(&self) -> Vec<bool> {
+        let mut result = vec![false; self.total_rules()];
+        for namespace in &self.namespaces {
+            for rule in &namespace.rules {
+                if rule.condition.eval(&self.matched_patterns) {
+                    result[rule.id as usize] = true;
+                } else if rule.global {
+                    for peer in &namespace.rules {
+                        result[peer.id as usize] = false;
+                    }
+                    break;
+                }
+            }
+        }
+        result
+    }
+
+    fn total_rules(&self) -> usize {
+        self.namespaces.iter().map(|n| n.rules.len()).sum()
+    }
+}
+
+/// Lowers `ruleset` into a [`WasmModuleBuilder`], returning the built
+/// module bytes.
+fn build_module(ruleset: &FuzzRuleset) -> Vec<u8> {
+    let mut builder = WasmModuleBuilder::new(false);
+    builder.namespaces_per_func(ruleset.namespaces_per_func);
+    builder.rules_per_func(ruleset.rules_per_func);
+
+    let check_for_pattern_match = builder.wasm_symbols().check_for_pattern_match;
+
+    for (i, namespace) in ruleset.namespaces.iter().enumerate() {
+        if i > 0 {
+            builder.new_namespace();
+        }
+        for rule in &namespace.rules {
+            let mut seq =
+                builder.start_rule(RuleId::from(rule.id as i32), 0..0, rule.global);
+            rule.condition.emit(&mut seq, check_for_pattern_match);
+            builder.finish_rule();
+        }
+    }
+
+    let (module, _rule_source_map) = builder.build();
+    module.emit_wasm()
+}
+
+/// Compiles, validates, instantiates and runs `wasm`, returning the
+/// per-rule match results the host-side `rule_match`/`rule_no_match` stubs
+/// observed, indexed by `RuleId`.
+fn run_module(wasm: &[u8], ruleset: &FuzzRuleset) -> Vec<bool> {
+    let engine = Engine::default();
+
+    // Validate before even trying to instantiate, so a validation failure
+    // and an instantiation/trap failure show up as distinct panics.
+    Module::validate(&engine, wasm)
+        .expect("WasmModuleBuilder produced an invalid module");
+    let module = Module::new(&engine, wasm).unwrap();
+
+    let mut store = Store::new(&engine, ());
+    let mut linker = Linker::new(&engine);
+
+    // `main_memory` holds the matched-patterns bitmap at offset 0, in the
+    // same bit layout `gen_check_for_pattern_match` expects: bit `n % 8` of
+    // byte `n / 8` is set when pattern `n` matched.
+    let memory = Memory::new(&mut store, MemoryType::new(1, None)).unwrap();
+    let mut bitmap = vec![0u8; (ruleset.num_patterns as usize).div_ceil(8)];
+    for (id, &matched) in ruleset.matched_patterns.iter().enumerate() {
+        if matched {
+            bitmap[id / 8] |= 1 << (id % 8);
+        }
+    }
+    memory.write(&mut store, 0, &bitmap).unwrap();
+    linker.define(&store, "yara_x", "main_memory", memory).unwrap();
+
+    linker
+        .define(
+            &store,
+            "yara_x",
+            "matching_patterns_bitmap_base",
+            wasmtime::Global::new(
+                &mut store,
+                wasmtime::GlobalType::new(
+                    wasmtime::ValType::I32,
+                    wasmtime::Mutability::Const,
+                ),
+                wasmtime::Val::I32(0),
+            )
+            .unwrap(),
+        )
+        .unwrap();
+    linker
+        .define(
+            &store,
+            "yara_x",
+            "filesize",
+            wasmtime::Global::new(
+                &mut store,
+                wasmtime::GlobalType::new(
+                    wasmtime::ValType::I64,
+                    wasmtime::Mutability::Var,
+                ),
+                wasmtime::Val::I64(0),
+            )
+            .unwrap(),
+        )
+        .unwrap();
+    linker
+        .define(
+            &store,
+            "yara_x",
+            "pattern_search_done",
+            wasmtime::Global::new(
+                &mut store,
+                wasmtime::GlobalType::new(
+                    wasmtime::ValType::I32,
+                    wasmtime::Mutability::Var,
+                ),
+                wasmtime::Val::I32(0),
+            )
+            .unwrap(),
+        )
+        .unwrap();
+
+    // `rule_match`/`rule_no_match` are the only host imports whose
+    // behavior this harness cares about: everything a rule's match status
+    // is observable through. Record every call; a global rule's
+    // `rule_no_match` call reverts its namespace's matches so far, exactly
+    // like `finish_rule`'s doc comment says the real implementation does.
+    let results = Rc::new(RefCell::new(vec![false; ruleset.total_rules()]));
+    let namespace_of: Vec<usize> = ruleset
+        .namespaces
+        .iter()
+        .enumerate()
+        .flat_map(|(ns, namespace)| {
+            namespace.rules.iter().map(move |_| ns)
+        })
+        .collect();
+    let rules_by_namespace: Vec<Vec<u32>> = ruleset
+        .namespaces
+        .iter()
+        .map(|n| n.rules.iter().map(|r| r.id).collect())
+        .collect();
+
+    for (module_name, field_name, ty) in module.imports().filter_map(|i| {
+        i.ty().func().map(|f| (i.module().to_string(), i.name().to_string(), f))
+    }) {
+        if module_name != "yara_x" {
+            continue;
+        }
+        if field_name.contains("rule_match") {
+            let results = results.clone();
+            linker
+                .func_wrap(
+                    "yara_x",
+                    &field_name,
+                    move |rule_id: i32| {
+                        results.borrow_mut()[rule_id as usize] = true;
+                    },
+                )
+                .unwrap();
+        } else if field_name.contains("rule_no_match") {
+            let results = results.clone();
+            let namespace_of = namespace_of.clone();
+            let rules_by_namespace = rules_by_namespace.clone();
+            linker
+                .func_wrap(
+                    "yara_x",
+                    &field_name,
+                    move |rule_id: i32| {
+                        let ns = namespace_of[rule_id as usize];
+                        let mut results = results.borrow_mut();
+                        for &peer in &rules_by_namespace[ns] {
+                            results[peer as usize] = false;
+                        }
+                    },
+                )
+                .unwrap();
+        } else {
+            // Not an import this harness's synthetic rule conditions ever
+            // call; it's left to `define_unknown_imports_as_default_values`
+            // below, which defines it as a function returning default values.
+            let _ = ty;
+        }
+    }
+
+    linker
+        .define_unknown_imports_as_default_values(&mut store, &module)
+        .unwrap();
+
+    let instance = linker.instantiate(&mut store, &module).unwrap();
+    let main = instance.get_typed_func::<(), i32>(&mut store, "main").unwrap();
+    let ret = main.call(&mut store, ()).unwrap();
+    assert!(ret == 0 || ret == 1, "`main` returned {ret}, expected 0 or 1");
+
+    // Invariant: `gen_check_for_pattern_match` reads the correct bitmap
+    // bit for every pattern id, checked directly and independently of any
+    // rule condition.
+    let check_for_pattern_match = instance
+        .get_typed_func::<i32, i32>(&mut store, "check_for_pattern_match")
+        .unwrap();
+    for (id, &matched) in ruleset.matched_patterns.iter().enumerate() {
+        let bit = check_for_pattern_match.call(&mut store, id as i32).unwrap();
+        assert_eq!(
+            bit != 0,
+            matched,
+            "check_for_pattern_match({id}) returned {bit}, bitmap says {matched}"
+        );
+    }
+
+    Rc::try_unwrap(results).unwrap().into_inner()
+}
+
+/// Entry point called by `fuzz/fuzz_targets/differential_wasm.rs`.
+pub fn run(data: &[u8]) {
+    let mut u = Unstructured::new(data);
+    let ruleset = match FuzzRuleset::new(&mut u) {
+        Ok(ruleset) => ruleset,
+        Err(_) => return,
+    };
+
+    let wasm = build_module(&ruleset);
+    let actual = run_module(&wasm, &ruleset);
+    let expected = ruleset.eval_reference();
+
+    assert_eq!(
+        actual, expected,
+        "WASM codegen and the reference evaluator disagree on rule matches"
+    );
+}
@@ -0,0 +1,9 @@
+//! Core YARA-X engine: file format parsers exposed to rules as modules,
+//! the WASM codegen backend used by the rule compiler, and (behind the
+//! `fuzzing` feature) a differential fuzzing harness for the latter.
+
+pub(crate) mod modules;
+pub(crate) mod wasm;
+
+#[cfg(feature = "fuzzing")]
+pub mod fuzzing;
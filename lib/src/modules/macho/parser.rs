@@ -16,10 +16,11 @@ use nom::bytes::complete::{tag, take, take_till};
 use nom::combinator::{cond, map, verify};
 use nom::error::ErrorKind;
 use nom::multi::{count, length_count};
-use nom::number::complete::{be_u32, le_u32, u16, u32, u64, u8};
+use nom::number::complete::{be_u32, i32, le_u32, u16, u32, u64, u8};
 use nom::number::Endianness;
 use nom::{Err, IResult, Parser};
-use protobuf::MessageField;
+use sha1::{Digest, Sha1};
+use sha2::Sha256;
 use x509_parser::x509::AlgorithmIdentifier;
 
 use crate::modules::protos;
@@ -39,10 +40,31 @@ const FAT_CIGAM: u32 = 0xbebafeca;
 const FAT_MAGIC_64: u32 = 0xcafebabf;
 const FAT_CIGAM_64: u32 = 0xbfbafeca;
 
+/// Magic string at the start of a Unix `ar` archive (static library). A
+/// member of a FAT binary can be an archive instead of a thin Mach-O file,
+/// in which case its members are Mach-O object files themselves.
+const AR_MAGIC: &[u8] = b"!<arch>\n";
+
+/// Size in bytes of the fixed-size header that precedes each member of an
+/// `ar` archive.
+const AR_HEADER_SIZE: usize = 60;
+
+/// Magic prefix at the start of a dyld shared cache file. The remaining
+/// bytes of the 16-byte magic field hold an architecture string (e.g.
+/// `"dyld_v1  arm64e"`).
+const DYLD_CACHE_MAGIC_PREFIX: &[u8] = b"dyld_v1";
+
+/// Upper bound on the `count` operand of
+/// `BIND_OPCODE_DO_BIND_ULEB_TIMES_SKIPPING_ULEB`. The real value is an
+/// attacker-controlled ULEB128 read straight from the scanned file, so
+/// without a cap a crafted binary could make `parse_imports` loop for an
+/// enormous number of iterations.
+const MAX_BIND_ULEB_TIMES_COUNT: u64 = 0x10000;
+
 /// Mach-O code signature constants
 const _CS_MAGIC_REQUIREMENT: u32 = 0xfade0c00;
 const _CS_MAGIC_REQUIREMENTS: u32 = 0xfade0c01;
-const _CS_MAGIC_CODEDIRECTORY: u32 = 0xfade0c02;
+const CS_MAGIC_CODEDIRECTORY: u32 = 0xfade0c02;
 const _CS_MAGIC_EMBEDDED_SIGNATURE: u32 = 0xfade0cc0;
 const _CS_MAGIC_DETACHED_SIGNATURE: u32 = 0xfade0cc1;
 const CS_MAGIC_BLOBWRAPPER: u32 = 0xfade0b01;
@@ -61,6 +83,20 @@ const N_SECT: u8 = 0xe; /* defined in section number n_sect */
 const _N_PBUD: u8 = 0xc; /* prebound undefined (defined in a dylib) */
 const N_INDR: u8 = 0xa; /* indirect */
 
+/// Mask for the section type bits of a section's `flags` field.
+const SECTION_TYPE: u32 = 0x000000ff;
+
+/// Section types whose contents are resolved through the indirect symbol
+/// table: stub functions, and lazy/non-lazy symbol pointers.
+const S_NON_LAZY_SYMBOL_POINTERS: u32 = 0x6;
+const S_LAZY_SYMBOL_POINTERS: u32 = 0x7;
+const S_SYMBOL_STUBS: u32 = 0x8;
+
+/// Sentinel values that can appear in the indirect symbol table in place
+/// of a real symbol table index.
+const INDIRECT_SYMBOL_LOCAL: u32 = 0x80000000;
+const INDIRECT_SYMBOL_ABS: u32 = 0x40000000;
+
 /// Mach-O export flag constants
 const EXPORT_SYMBOL_FLAGS_WEAK_DEFINITION: u64 = 0x00000004;
 const EXPORT_SYMBOL_FLAGS_REEXPORT: u64 = 0x00000008;
@@ -69,20 +105,45 @@ const EXPORT_SYMBOL_FLAGS_STUB_AND_RESOLVER: u64 = 0x00000010;
 /// Mach-O import opcode constants
 const BIND_OPCODE_MASK: u8 = 0xF0;
 const BIND_IMMEDIATE_MASK: u8 = 0x0F;
-const _BIND_OPCODE_DONE: u8 = 0x00;
-const _BIND_OPCODE_SET_DYLIB_ORDINAL_IMM: u8 = 0x10;
+const BIND_OPCODE_DONE: u8 = 0x00;
+const BIND_OPCODE_SET_DYLIB_ORDINAL_IMM: u8 = 0x10;
 const BIND_OPCODE_SET_DYLIB_ORDINAL_ULEB: u8 = 0x20;
-const _BIND_OPCODE_SET_DYLIB_SPECIAL_IMM: u8 = 0x30;
+const BIND_OPCODE_SET_DYLIB_SPECIAL_IMM: u8 = 0x30;
 const BIND_OPCODE_SET_SYMBOL_TRAILING_FLAGS_IMM: u8 = 0x40;
-const _BIND_OPCODE_SET_TYPE_IMM: u8 = 0x50;
+const BIND_OPCODE_SET_TYPE_IMM: u8 = 0x50;
 const BIND_OPCODE_SET_ADDEND_SLEB: u8 = 0x60;
 const BIND_OPCODE_SET_SEGMENT_AND_OFFSET_ULEB: u8 = 0x70;
 const BIND_OPCODE_ADD_ADDR_ULEB: u8 = 0x80;
-const _BIND_OPCODE_DO_BIND: u8 = 0x90;
+const BIND_OPCODE_DO_BIND: u8 = 0x90;
 const BIND_OPCODE_DO_BIND_ADD_ADDR_ULEB: u8 = 0xA0;
-const _BIND_OPCODE_DO_BIND_ADD_ADDR_IMM_SCALED: u8 = 0xB0;
+const BIND_OPCODE_DO_BIND_ADD_ADDR_IMM_SCALED: u8 = 0xB0;
 const BIND_OPCODE_DO_BIND_ULEB_TIMES_SKIPPING_ULEB: u8 = 0xC0;
 
+/// Bit set in the immediate of `BIND_OPCODE_SET_SYMBOL_TRAILING_FLAGS_IMM`
+/// when the symbol being bound is a weak import.
+const BIND_SYMBOL_FLAGS_WEAK_IMPORT: u8 = 0x1;
+
+/// Mach-O relocation constant. When set in the first word of a relocation
+/// entry, the entry is a "scattered" relocation rather than a "generic" one.
+const R_SCATTERED: u32 = 0x80000000;
+
+/// `dyld_chained_ptr_format` values, selecting the bit layout of each
+/// pointer walked while following a `dyld_chained_starts_in_segment` chain.
+const DYLD_CHAINED_PTR_ARM64E: u16 = 1;
+const DYLD_CHAINED_PTR_64: u16 = 2;
+const DYLD_CHAINED_PTR_64_OFFSET: u16 = 6;
+const DYLD_CHAINED_PTR_ARM64E_USERLAND: u16 = 9;
+const DYLD_CHAINED_PTR_ARM64E_USERLAND24: u16 = 12;
+
+/// Sentinel `page_start` value meaning that a page has no chained fixups.
+const DYLD_CHAINED_PTR_START_NONE: u16 = 0xFFFF;
+
+/// `dyld_chained_import_format` values, selecting the on-disk layout (and
+/// therefore the size) of each entry in the chained-fixups imports table.
+const DYLD_CHAINED_IMPORT: u32 = 1;
+const DYLD_CHAINED_IMPORT_ADDEND: u32 = 2;
+const DYLD_CHAINED_IMPORT_ADDEND64: u32 = 3;
+
 /// Mach-O dynamic linker constant
 const LC_REQ_DYLD: u32 = 0x80000000;
 
@@ -140,8 +201,47 @@ pub struct MachO<'a> {
     archs: Vec<FatArch>,
     /// This array contains an entry per architecture included in the Mach-O
     /// file. For single-architecture binaries the array contains a single
-    /// entry.
-    files: Vec<MachOFile<'a>>,
+    /// entry. Each entry is either a thin Mach-O file, or an `ar` archive
+    /// (static library) containing one or more Mach-O object files.
+    files: Vec<SingleArch<'a>>,
+    /// When representing a dyld shared cache, this array contains one entry
+    /// per image (dylib) found in the cache. For any other kind of Mach-O
+    /// file this array is empty.
+    images: Vec<MachOFile<'a>>,
+}
+
+/// The content found at the offset described by a [`FatArch`] entry.
+///
+/// Most FAT binaries embed a thin Mach-O file per architecture, but some
+/// (notably static libraries distributed as FAT binaries) embed an `ar`
+/// archive whose members are themselves Mach-O object files.
+enum SingleArch<'a> {
+    MachO(MachOFile<'a>),
+    Archive(Vec<MachOFile<'a>>),
+}
+
+/// A `dyld_cache_mapping_info` entry, describing a region of the dyld
+/// shared cache file and the virtual address range it's mapped to.
+struct DyldCacheMapping {
+    address: u64,
+    size: u64,
+    file_offset: u64,
+    #[allow(dead_code)]
+    max_prot: u32,
+    #[allow(dead_code)]
+    init_prot: u32,
+}
+
+/// A `dyld_cache_image_info` entry, describing a single image (dylib)
+/// embedded in the dyld shared cache.
+struct DyldCacheImage {
+    address: u64,
+    #[allow(dead_code)]
+    mod_time: u64,
+    #[allow(dead_code)]
+    inode: u64,
+    #[allow(dead_code)]
+    path_file_offset: u32,
 }
 
 impl<'a> MachO<'a> {
@@ -157,7 +257,8 @@ impl<'a> MachO<'a> {
             Ok(Self {
                 fat_magic: None,
                 archs: Vec::new(),
-                files: vec![Self::parse_macho_file(data)?],
+                files: vec![SingleArch::MachO(Self::parse_macho_file(data)?)],
+                images: Vec::new(),
             })
         }
     }
@@ -235,20 +336,219 @@ impl<'a> MachO<'a> {
             let start = arch.offset as usize;
             let end = start.saturating_add(arch.size as usize);
 
-            if let Some(macho) = data.get(start..end) {
-                match Self::parse_macho_file(macho) {
-                    Ok(macho) => files.push(macho),
-                    #[cfg(feature = "logging")]
-                    Err(err) => {
-                        error!("Error parsing Mach-O file: {:?}", err);
+            if let Some(member) = data.get(start..end) {
+                if member.starts_with(AR_MAGIC) {
+                    files.push(SingleArch::Archive(Self::parse_archive(
+                        member,
+                    )));
+                } else {
+                    match Self::parse_macho_file(member) {
+                        Ok(macho) => files.push(SingleArch::MachO(macho)),
+                        #[cfg(feature = "logging")]
+                        Err(err) => {
+                            error!("Error parsing Mach-O file: {:?}", err);
+                        }
+                        #[cfg(not(feature = "logging"))]
+                        Err(_) => {}
                     }
-                    #[cfg(not(feature = "logging"))]
-                    Err(_) => {}
                 }
             };
         }
 
-        Ok(MachO { fat_magic: Some(magic), archs, files })
+        Ok(MachO { fat_magic: Some(magic), archs, files, images: Vec::new() })
+    }
+
+    /// Parses the `dyld_cache_mapping_info` table out of a dyld shared cache
+    /// file's header: the magic plus `mappingOffset`/`mappingCount` fields
+    /// at the fixed location shared by the main cache and every split
+    /// sub-cache file. Each cache file has its own mapping table, giving a
+    /// file-local `fileOffset` for each virtual address range it covers.
+    fn parse_dyld_cache_mappings<'b>(
+        data: &'b [u8],
+    ) -> Result<Vec<DyldCacheMapping>, Err<NomError<'b>>> {
+        let (_, magic) = take(16_usize)(data)?;
+
+        if !magic.starts_with(DYLD_CACHE_MAGIC_PREFIX) {
+            return Err(Err::Error(NomError::new(data, ErrorKind::Tag)));
+        }
+
+        let (_, (mapping_offset, mapping_count)) = (
+            u32(Endianness::Little), // mappingOffset
+            u32(Endianness::Little), // mappingCount
+        )
+            .parse(&data[16..])?;
+
+        let eof_err = || Err::Error(NomError::new(data, ErrorKind::Eof));
+
+        let mapping_data =
+            data.get(mapping_offset as usize..).ok_or_else(eof_err)?;
+
+        let (_, mappings) = count(
+            map(
+                (
+                    u64(Endianness::Little), // address
+                    u64(Endianness::Little), // size
+                    u64(Endianness::Little), // fileOffset
+                    u32(Endianness::Little), // maxProt
+                    u32(Endianness::Little), // initProt
+                ),
+                |(address, size, file_offset, max_prot, init_prot)| {
+                    DyldCacheMapping {
+                        address,
+                        size,
+                        file_offset,
+                        max_prot,
+                        init_prot,
+                    }
+                },
+            ),
+            mapping_count as usize,
+        )
+        .parse(mapping_data)?;
+
+        Ok(mappings)
+    }
+
+    /// Parses a dyld shared cache file, returning a [`MachO`] whose `images`
+    /// field holds one entry per image (dylib) found in the cache.
+    ///
+    /// The dyld shared cache stores system libraries merged into one or
+    /// more large files. `sub_caches` lets the caller provide the content
+    /// of any split sub-cache files referenced by the main cache (e.g. the
+    /// `.1` and `.symbols` companion files), so that images whose segments
+    /// live in one of them can still be resolved. Each sub-cache has its
+    /// own header and mapping table with file-local offsets, so an image's
+    /// virtual address is resolved against whichever cache's own mappings
+    /// actually cover it, rather than reusing the main cache's offset math.
+    pub fn parse_dyld_shared_cache(
+        data: &'a [u8],
+        sub_caches: &[&'a [u8]],
+    ) -> Result<Self, Err<NomError<'a>>> {
+        let mappings = Self::parse_dyld_cache_mappings(data)?;
+
+        let (_, (images_offset, images_count)) = (
+            u32(Endianness::Little), // imagesOffset
+            u32(Endianness::Little), // imagesCount
+        )
+            // mappingOffset/mappingCount, already parsed above.
+            .parse(&data[24..])?;
+
+        let eof_err = || Err::Error(NomError::new(data, ErrorKind::Eof));
+
+        let images_data =
+            data.get(images_offset as usize..).ok_or_else(eof_err)?;
+
+        let (_, image_infos) = count(
+            map(
+                (
+                    u64(Endianness::Little), // address
+                    u64(Endianness::Little), // modTime
+                    u64(Endianness::Little), // inode
+                    u32(Endianness::Little), // pathFileOffset
+                    u32(Endianness::Little), // pad
+                ),
+                |(address, mod_time, inode, path_file_offset, _pad)| {
+                    DyldCacheImage { address, mod_time, inode, path_file_offset }
+                },
+            ),
+            images_count as usize,
+        )
+        .parse(images_data)?;
+
+        // The main cache plus any split sub-caches supplied by the caller,
+        // each paired with its own mapping table. A sub-cache whose header
+        // doesn't parse as a dyld cache is skipped.
+        let mut caches: Vec<(&'a [u8], Vec<DyldCacheMapping>)> =
+            vec![(data, mappings)];
+
+        for sub_cache in sub_caches {
+            if let Ok(sub_mappings) = Self::parse_dyld_cache_mappings(sub_cache)
+            {
+                caches.push((sub_cache, sub_mappings));
+            }
+        }
+
+        let mut images = Vec::new();
+
+        for image in &image_infos {
+            for (cache, cache_mappings) in &caches {
+                let Some(file_offset) = cache_mappings.iter().find_map(|m| {
+                    let end = m.address.saturating_add(m.size);
+                    if image.address >= m.address && image.address < end {
+                        Some(
+                            m.file_offset
+                                .saturating_add(image.address - m.address),
+                        )
+                    } else {
+                        None
+                    }
+                }) else {
+                    continue;
+                };
+
+                if let Some(image_data) = cache.get(file_offset as usize..) {
+                    if let Ok(macho_file) = Self::parse_macho_file(image_data)
+                    {
+                        images.push(macho_file);
+                        break;
+                    }
+                }
+            }
+        }
+
+        Ok(Self {
+            fat_magic: None,
+            archs: Vec::new(),
+            files: Vec::new(),
+            images,
+        })
+    }
+
+    /// Parses a Unix `ar` archive (static library), returning the Mach-O
+    /// objects contained in its members. Members that aren't valid Mach-O
+    /// files, or that are truncated, are skipped.
+    fn parse_archive(data: &'a [u8]) -> Vec<MachOFile<'a>> {
+        let mut files = Vec::new();
+        let mut cursor = AR_MAGIC.len();
+
+        while let Some(header) =
+            data.get(cursor..cursor.saturating_add(AR_HEADER_SIZE))
+        {
+            if header.len() < AR_HEADER_SIZE || &header[58..60] != b"\x60\x0a"
+            {
+                break;
+            }
+
+            let name = &header[0..16];
+            let size: usize = std::str::from_utf8(&header[48..58])
+                .ok()
+                .and_then(|s| s.trim().parse().ok())
+                .unwrap_or(0);
+
+            let member_start = cursor + AR_HEADER_SIZE;
+            let member_end = member_start.saturating_add(size);
+
+            // The BSD extended-name convention (`#1/<len>`) stores the
+            // member's real name in the first `len` bytes of its data,
+            // right before the actual content.
+            let content_start = std::str::from_utf8(name)
+                .ok()
+                .map(|n| n.trim_end())
+                .and_then(|n| n.strip_prefix("#1/"))
+                .and_then(|len| len.trim().parse::<usize>().ok())
+                .map_or(member_start, |len| member_start.saturating_add(len));
+
+            if let Some(member_data) = data.get(content_start..member_end) {
+                if let Ok(macho) = Self::parse_macho_file(member_data) {
+                    files.push(macho);
+                }
+            }
+
+            // Archive members are padded with a newline to an even size.
+            cursor = member_end.saturating_add(size % 2);
+        }
+
+        files
     }
 
     /// Parses a single-architecture Mach-O file.
@@ -272,7 +572,7 @@ impl<'a> MachO<'a> {
             _ => unreachable!(),
         };
 
-        let (mut commands, header) = map(
+        let (commands, header) = map(
             (
                 u32(endianness),                    // cputype
                 u32(endianness),                    // cpusubtype
@@ -326,34 +626,34 @@ impl<'a> MachO<'a> {
             code_signature_data: None,
             entitlements: Vec::new(),
             certificates: Vec::new(),
+            identifier: None,
+            team_id: None,
+            cdhash: None,
+            hash_type: None,
+            cs_flags: None,
             uuid: None,
             build_version: None,
             min_version: None,
             exports: Vec::new(),
             imports: Vec::new(),
+            binds: Vec::new(),
         };
 
-        for _ in 0..macho.header.ncmds as usize {
-            match macho.command().parse(commands) {
-                Ok((c, _)) => commands = c,
-                Err(err) => {
-                    #[cfg(feature = "logging")]
-                    error!("Error parsing Mach-O file: {:?}", err);
-                    // Break the loop when the end of file has been reached.
-                    // With other types of errors we keep trying to parse more
-                    // commands as one individual command structure could be
-                    // corrupted while the rest are ok. But when the end of
-                    // the file is reached there are no more commands that can
-                    // be parsed.
-                    if let Err::Error(e) = err {
-                        if e.code == ErrorKind::Eof {
-                            break;
-                        }
-                    }
-                }
+        for (cmd, _cmdsize, cmd_data) in LoadCommandIterator::new(
+            commands,
+            endianness,
+            macho.header.sizeofcmds,
+        ) {
+            if let Err(err) = macho.populate_command(cmd, cmd_data) {
+                #[cfg(feature = "logging")]
+                error!("Error parsing Mach-O file: {:?}", err);
+                // Other commands could still be well-formed even if one of
+                // them is corrupted, so parsing continues with the next one.
             }
         }
 
+        macho.parse_relocations(data);
+
         if let Some(ref symtab) = macho.symtab {
             let str_offset = symtab.stroff as usize;
             let str_end = symtab.strsize as usize;
@@ -375,6 +675,15 @@ impl<'a> MachO<'a> {
                         // everything else
                     };
                 }
+
+                if let Some(ref dysymtab) = macho.dysymtab {
+                    macho.resolve_indirect_symbols(
+                        data,
+                        string_table,
+                        sym_offset,
+                        dysymtab.indirectsymoff as usize,
+                    );
+                }
             }
         }
 
@@ -492,10 +801,23 @@ pub struct MachOFile<'a> {
     code_signature_data: Option<LinkedItData>,
     entitlements: Vec<String>,
     certificates: Vec<Certificate>,
+    /// Bundle identifier read from the CodeDirectory blob's `identOffset`.
+    identifier: Option<String>,
+    /// Team identifier read from the CodeDirectory blob's `teamOffset`,
+    /// present when `version >= 0x20200`.
+    team_id: Option<String>,
+    /// `cdhash`, the hash of the whole CodeDirectory blob, truncated to 20
+    /// bytes as Apple does.
+    cdhash: Option<Vec<u8>>,
+    /// Algorithm used to compute `cdhash` (1 = SHA-1, 2 = SHA-256).
+    hash_type: Option<u8>,
+    /// Signing flags from the CodeDirectory blob.
+    cs_flags: Option<u32>,
     build_version: Option<BuildVersionCommand>,
     min_version: Option<MinVersion>,
-    exports: Vec<String>,
+    exports: Vec<Export>,
     imports: Vec<String>,
+    binds: Vec<Bind>,
 }
 
 impl MachOFile<'_> {
@@ -510,6 +832,16 @@ impl MachOFile<'_> {
         }
         None
     }
+
+    /// Size in bytes of a pointer in this Mach-O file, as used when
+    /// advancing through the LC_DYLD_INFO bind opcode stream.
+    fn pointer_size(&self) -> u64 {
+        if self.is_32_bits {
+            4
+        } else {
+            8
+        }
+    }
 }
 
 impl<'a> MachOFile<'a> {
@@ -566,137 +898,157 @@ impl<'a> MachOFile<'a> {
                     reserved1,
                     reserved2,
                     reserved3,
+                    relocations: Vec::new(),
                 }
             },
         )
     }
 
-    /// Parser that parses a Mach-O command.
-    fn command(
-        &mut self,
-    ) -> impl Parser<&'a [u8], Output = (), Error = NomError<'a>> + '_ {
-        move |input: &'a [u8]| {
-            // The first two u32 in the command are the value that indicates
-            // the command type, and the size of the command's data.
-            let (remainder, (command, command_size)) = (
-                u32(self.endianness), // command
-                u32(self.endianness), // command_size
-            )
-                .parse(input)?;
+    /// Parses the relocation entries referenced by each section's `reloff`
+    /// and `nreloc` fields.
+    fn parse_relocations(&mut self, data: &'a [u8]) {
+        let endianness = self.endianness;
 
-            // Take the command's data.
-            let (remainder, command_data) = take(
-                // `command_size` includes the sizes of `command` and
-                // `command_size` itself, which is 8 bytes in total. So,
-                // the size of the command's data is actually `command_size`
-                // minus 8.
-                command_size.saturating_sub(8),
-            )(remainder)?;
-            // Parse the command's data. Parsers for individual commands must
-            // consume all `command_data`.
-            match command {
-                LC_MAIN => {
-                    let (_, (entry_point_offset, stack_size)) =
-                        self.main_command().parse(command_data)?;
-                    self.entry_point_offset = Some(entry_point_offset);
-                    self.stack_size = Some(stack_size);
-                }
-                LC_UNIXTHREAD => {
-                    let (_, eip) =
-                        self.thread_command().parse(command_data)?;
-                    self.entry_point_rva = Some(eip);
-                }
-                LC_SEGMENT | LC_SEGMENT_64 => {
-                    let (_, segment) =
-                        self.segment_command().parse(command_data)?;
-                    self.segments.push(segment);
-                }
-                LC_RPATH => {
-                    let (_, rpath) =
-                        self.rpath_command().parse(command_data)?;
-                    self.rpaths.push(rpath);
-                }
-                LC_LOAD_DYLIB | LC_ID_DYLIB | LC_LOAD_WEAK_DYLIB
-                | LC_REEXPORT_DYLIB => {
-                    let (_, dylib) =
-                        self.dylib_command().parse(command_data)?;
-                    self.dylibs.push(dylib);
-                }
-                LC_SOURCE_VERSION => {
-                    let (_, ver) =
-                        self.source_version_command().parse(command_data)?;
-                    self.source_version =
-                        Some(convert_to_source_version_string(ver));
-                }
-                LC_ID_DYLINKER | LC_LOAD_DYLINKER | LC_DYLD_ENVIRONMENT => {
-                    let (_, dylinker) =
-                        self.dylinker_command().parse(command_data)?;
-                    self.dynamic_linker = Some(dylinker);
-                }
-                LC_SYMTAB => {
-                    let (_, symtab) =
-                        self.symtab_command().parse(command_data)?;
-                    self.symtab = Some(symtab);
-                }
-                LC_DYSYMTAB => {
-                    let (_, dysymtab) =
-                        self.dysymtab_command().parse(command_data)?;
-                    self.dysymtab = Some(dysymtab);
-                }
-                LC_CODE_SIGNATURE => {
-                    let (_, lid) =
-                        self.linkeditdata_command().parse(command_data)?;
-                    self.code_signature_data = Some(lid);
-                }
-                LC_DYLD_EXPORTS_TRIE => {
-                    let (_, exports_data) =
-                        self.linkeditdata_command().parse(command_data)?;
-                    self.dyld_export_trie = Some(DyldExportTrie {
-                        data_off: exports_data.dataoff,
-                        data_size: exports_data.datasize,
-                    });
+        for segment in self.segments.iter_mut() {
+            for section in segment.sections.iter_mut() {
+                if section.nreloc == 0 {
+                    continue;
                 }
-                LC_DYLD_CHAINED_FIXUPS => {
-                    let (_, imports_data) =
-                        self.linkeditdata_command().parse(command_data)?;
-                    self.dyld_chain_fixups = Some(DyldChainFixups {
-                        data_off: imports_data.dataoff,
-                        data_size: imports_data.datasize,
-                    });
-                }
-                LC_DYLD_INFO | LC_DYLD_INFO_ONLY => {
-                    let (_, dyld_info) =
-                        self.dyld_info_command().parse(command_data)?;
-                    self.dyld_info = Some(dyld_info);
-                }
-                LC_UUID => {
-                    let (_, uuid) = self.uuid_command().parse(command_data)?;
-                    self.uuid = Some(uuid);
-                }
-                LC_BUILD_VERSION => {
-                    let (_, bv) =
-                        self.build_version_command().parse(command_data)?;
-                    self.build_version = Some(bv);
-                }
-                LC_VERSION_MIN_MACOSX
-                | LC_VERSION_MIN_IPHONEOS
-                | LC_VERSION_MIN_TVOS
-                | LC_VERSION_MIN_WATCHOS => {
-                    let (_, mut mv) =
-                        self.min_version_command().parse(command_data)?;
-                    mv.device = command;
-                    self.min_version = Some(mv);
-                }
-                LC_LINKER_OPTION => {
-                    let (_, linker_options) =
-                        self.linker_options_command().parse(command_data)?;
-                    self.linker_options.extend(linker_options);
+
+                let offset = section.reloff as usize;
+                let size = (section.nreloc as usize).saturating_mul(8);
+
+                let Some(mut reloc_data) =
+                    data.get(offset..offset.saturating_add(size))
+                else {
+                    continue;
+                };
+
+                for _ in 0..section.nreloc {
+                    match relocation(endianness).parse(reloc_data) {
+                        Ok((remainder, reloc)) => {
+                            section.relocations.push(reloc);
+                            reloc_data = remainder;
+                        }
+                        Err(_) => break,
+                    }
                 }
-                _ => {}
             }
+        }
+    }
 
-            Ok((remainder, ()))
+    /// Populates `self` from a single load command, given its type and the
+    /// bytes that follow its 8-byte header, as produced by a
+    /// [`LoadCommandIterator`]. Unknown command types are ignored.
+    fn populate_command(
+        &mut self,
+        command: u32,
+        command_data: &'a [u8],
+    ) -> Result<(), Err<NomError<'a>>> {
+        // Parse the command's data. Parsers for individual commands must
+        // consume all `command_data`.
+        match command {
+            LC_MAIN => {
+                let (_, (entry_point_offset, stack_size)) =
+                    self.main_command().parse(command_data)?;
+                self.entry_point_offset = Some(entry_point_offset);
+                self.stack_size = Some(stack_size);
+            }
+            LC_UNIXTHREAD => {
+                let (_, eip) =
+                    self.thread_command().parse(command_data)?;
+                self.entry_point_rva = Some(eip);
+            }
+            LC_SEGMENT | LC_SEGMENT_64 => {
+                let (_, segment) =
+                    self.segment_command().parse(command_data)?;
+                self.segments.push(segment);
+            }
+            LC_RPATH => {
+                let (_, rpath) =
+                    self.rpath_command().parse(command_data)?;
+                self.rpaths.push(rpath);
+            }
+            LC_LOAD_DYLIB | LC_ID_DYLIB | LC_LOAD_WEAK_DYLIB
+            | LC_REEXPORT_DYLIB => {
+                let (_, dylib) =
+                    self.dylib_command().parse(command_data)?;
+                self.dylibs.push(dylib);
+            }
+            LC_SOURCE_VERSION => {
+                let (_, ver) =
+                    self.source_version_command().parse(command_data)?;
+                self.source_version =
+                    Some(convert_to_source_version_string(ver));
+            }
+            LC_ID_DYLINKER | LC_LOAD_DYLINKER | LC_DYLD_ENVIRONMENT => {
+                let (_, dylinker) =
+                    self.dylinker_command().parse(command_data)?;
+                self.dynamic_linker = Some(dylinker);
+            }
+            LC_SYMTAB => {
+                let (_, symtab) =
+                    self.symtab_command().parse(command_data)?;
+                self.symtab = Some(symtab);
+            }
+            LC_DYSYMTAB => {
+                let (_, dysymtab) =
+                    self.dysymtab_command().parse(command_data)?;
+                self.dysymtab = Some(dysymtab);
+            }
+            LC_CODE_SIGNATURE => {
+                let (_, lid) =
+                    self.linkeditdata_command().parse(command_data)?;
+                self.code_signature_data = Some(lid);
+            }
+            LC_DYLD_EXPORTS_TRIE => {
+                let (_, exports_data) =
+                    self.linkeditdata_command().parse(command_data)?;
+                self.dyld_export_trie = Some(DyldExportTrie {
+                    data_off: exports_data.dataoff,
+                    data_size: exports_data.datasize,
+                });
+            }
+            LC_DYLD_CHAINED_FIXUPS => {
+                let (_, imports_data) =
+                    self.linkeditdata_command().parse(command_data)?;
+                self.dyld_chain_fixups = Some(DyldChainFixups {
+                    data_off: imports_data.dataoff,
+                    data_size: imports_data.datasize,
+                });
+            }
+            LC_DYLD_INFO | LC_DYLD_INFO_ONLY => {
+                let (_, dyld_info) =
+                    self.dyld_info_command().parse(command_data)?;
+                self.dyld_info = Some(dyld_info);
+            }
+            LC_UUID => {
+                let (_, uuid) = self.uuid_command().parse(command_data)?;
+                self.uuid = Some(uuid);
+            }
+            LC_BUILD_VERSION => {
+                let (_, bv) =
+                    self.build_version_command().parse(command_data)?;
+                self.build_version = Some(bv);
+            }
+            LC_VERSION_MIN_MACOSX
+            | LC_VERSION_MIN_IPHONEOS
+            | LC_VERSION_MIN_TVOS
+            | LC_VERSION_MIN_WATCHOS => {
+                let (_, mut mv) =
+                    self.min_version_command().parse(command_data)?;
+                mv.device = command;
+                self.min_version = Some(mv);
+            }
+            LC_LINKER_OPTION => {
+                let (_, linker_options) =
+                    self.linker_options_command().parse(command_data)?;
+                self.linker_options.extend(linker_options);
+            }
+            _ => {}
         }
+
+        Ok(())
     }
 
     /// Parser that parses a LC_MAIN command.
@@ -1054,6 +1406,142 @@ impl<'a> MachOFile<'a> {
                             }
                         }
                     }
+                    CS_MAGIC_CODEDIRECTORY => {
+                        let Some(cd_body) = super_data.get(
+                            offset.saturating_add(size_of_blob)
+                                ..offset.saturating_add(length),
+                        ) else {
+                            continue;
+                        };
+
+                        let Ok((
+                            remainder,
+                            (
+                                version,
+                                flags,
+                                _hash_offset,
+                                ident_offset,
+                                _n_special_slots,
+                                _n_code_slots,
+                                _code_limit,
+                                _hash_size,
+                                hash_type,
+                                _platform,
+                                _page_size,
+                            ),
+                        )) = (
+                            u32(Endianness::Big), // version
+                            u32(Endianness::Big), // flags
+                            u32(Endianness::Big), // hashOffset
+                            u32(Endianness::Big), // identOffset
+                            u32(Endianness::Big), // nSpecialSlots
+                            u32(Endianness::Big), // nCodeSlots
+                            u32(Endianness::Big), // codeLimit
+                            u8,                    // hashSize
+                            u8,                    // hashType
+                            u8,                    // platform
+                            u8,                    // pageSize
+                        )
+                            .parse(cd_body)
+                        else {
+                            continue;
+                        };
+
+                        // `spare2` was added in version 0x20100, and
+                        // `scatterOffset` right after it in version
+                        // 0x20200; both must be skipped before reaching
+                        // `teamOffset`, which was also added in 0x20200.
+                        let mut team_offset = None;
+                        let mut after_spare2 = remainder;
+
+                        if version >= 0x20100 {
+                            let Ok((r, _spare2)) =
+                                u32(Endianness::Big)(remainder)
+                            else {
+                                continue;
+                            };
+                            after_spare2 = r;
+                        }
+
+                        if version >= 0x20200 {
+                            if let Ok((r, _scatter_offset)) =
+                                u32(Endianness::Big)(after_spare2)
+                            {
+                                team_offset =
+                                    u32(Endianness::Big)(r).ok().map(
+                                        |(_, team_offset)| team_offset,
+                                    );
+                            }
+                        }
+
+                        self.cs_flags = Some(flags);
+                        self.hash_type = Some(hash_type);
+
+                        if let Some((_, ident)) = super_data
+                            .get(
+                                offset.saturating_add(ident_offset as usize)
+                                    ..offset.saturating_add(length),
+                            )
+                            .and_then(|ident_data| {
+                                map(
+                                    (
+                                        take_till(|b| b == b'\x00'),
+                                        tag("\x00"),
+                                    ),
+                                    |(s, _)| s,
+                                )
+                                .parse(ident_data)
+                                .ok()
+                            })
+                        {
+                            if let Ok(ident) = ident.to_str() {
+                                self.identifier = Some(ident.to_string());
+                            }
+                        }
+
+                        if let Some(team_offset) = team_offset {
+                            if let Some((_, team)) = super_data
+                                .get(
+                                    offset.saturating_add(
+                                        team_offset as usize,
+                                    )..offset.saturating_add(length),
+                                )
+                                .and_then(|team_data| {
+                                    map(
+                                        (
+                                            take_till(|b| b == b'\x00'),
+                                            tag("\x00"),
+                                        ),
+                                        |(s, _)| s,
+                                    )
+                                    .parse(team_data)
+                                    .ok()
+                                })
+                            {
+                                if let Ok(team) = team.to_str() {
+                                    self.team_id = Some(team.to_string());
+                                }
+                            }
+                        }
+
+                        if let Some(cd_blob) = super_data
+                            .get(offset..offset.saturating_add(length))
+                        {
+                            self.cdhash = match hash_type {
+                                1 => {
+                                    let mut hasher = Sha1::new();
+                                    hasher.update(cd_blob);
+                                    Some(hasher.finalize()[..20].to_vec())
+                                }
+                                2 => {
+                                    let mut hasher = Sha256::new();
+                                    hasher.update(cd_blob);
+                                    Some(hasher.finalize()[..20].to_vec())
+                                }
+                                _ => None,
+                            };
+                        }
+                    }
                     _ => {}
                 }
             }
@@ -1168,6 +1656,97 @@ impl<'a> MachOFile<'a> {
         Ok((data, ()))
     }
 
+    /// Resolves the indirect symbol table entries referenced by
+    /// `__stubs`/`__la_symbol_ptr`/`__nl_symbol_ptr`-like sections, emitting
+    /// one import per resolved symbol. These sections don't appear in the
+    /// LC_DYLD_INFO bind stream or the export trie, so binaries linked the
+    /// classic way (without DYLD_INFO/chained fixups) would otherwise
+    /// report no imports for them at all.
+    fn resolve_indirect_symbols(
+        &mut self,
+        data: &'a [u8],
+        string_table: &'a [u8],
+        symbol_table_offset: usize,
+        indirect_symtab_offset: usize,
+    ) {
+        let nlist_size: usize = if self.is_32_bits { 12 } else { 16 };
+
+        for section in self
+            .segments
+            .iter()
+            .flat_map(|segment| segment.sections.iter())
+        {
+            let section_type = section.flags & SECTION_TYPE;
+
+            let stride = match section_type {
+                S_SYMBOL_STUBS => section.reserved2 as u64,
+                S_NON_LAZY_SYMBOL_POINTERS | S_LAZY_SYMBOL_POINTERS => {
+                    self.pointer_size()
+                }
+                _ => continue,
+            };
+
+            if stride == 0 {
+                continue;
+            }
+
+            let entry_count = section.size / stride;
+
+            for i in 0..entry_count {
+                let Some(indirect_index) =
+                    section.reserved1.checked_add(i as u32)
+                else {
+                    break;
+                };
+
+                let indirect_entry_offset = indirect_symtab_offset
+                    .saturating_add(indirect_index as usize * 4);
+
+                let Some((_, sym_index)) = data
+                    .get(indirect_entry_offset..indirect_entry_offset + 4)
+                    .and_then(|b| u32(self.endianness)(b).ok())
+                else {
+                    continue;
+                };
+
+                if sym_index & (INDIRECT_SYMBOL_LOCAL | INDIRECT_SYMBOL_ABS)
+                    != 0
+                {
+                    continue;
+                }
+
+                let nlist_offset = symbol_table_offset
+                    .saturating_add(sym_index as usize * nlist_size);
+
+                let Some((_, n)) = data
+                    .get(nlist_offset..)
+                    .and_then(|b| self.nlist().parse(b).ok())
+                else {
+                    continue;
+                };
+
+                let Some(name) = string_table
+                    .get(n.n_strx as usize..)
+                    .and_then(|s| {
+                        map(
+                            (take_till(|b| b == b'\x00'), tag("\x00")),
+                            |(s, _)| BStr::new(s),
+                        )
+                        .parse(s)
+                        .ok()
+                    })
+                    .and_then(|(_, s)| s.to_str().ok())
+                else {
+                    continue;
+                };
+
+                if !name.is_empty() {
+                    self.imports.push(name.to_string());
+                }
+            }
+        }
+    }
+
     /// Parser that parses the exports at the offsets defined within
     /// LC_DYLD_INFO, LC_DYLD_INFO_ONLY, and LC_DYLD_EXPORTS_TRIE.
     fn parse_exports(&mut self, data: &'a [u8]) -> IResult<&'a [u8], ()> {
@@ -1191,29 +1770,44 @@ impl<'a> MachOFile<'a> {
 
             let (mut remaining_data, length) = uleb128(node_data)?;
 
+            let mut export_flags = 0u64;
+            let mut address = None;
+            let mut reexport_dylib = None;
+            let mut reexport_name = None;
+            let mut stub_offset = None;
+            let mut resolver_offset = None;
+
             if length != 0 {
                 let (remainder, flags) = uleb128(remaining_data)?;
+                export_flags = flags;
+
                 match flags {
                     EXPORT_SYMBOL_FLAGS_STUB_AND_RESOLVER => {
-                        let (remainder, (_stub_offset, _resolver_offset)) =
+                        let (remainder, (stub, resolver)) =
                             (uleb128, uleb128).parse(remainder)?;
+                        stub_offset = Some(stub);
+                        resolver_offset = Some(resolver);
                         remaining_data = remainder;
                     }
                     EXPORT_SYMBOL_FLAGS_REEXPORT => {
-                        let (remainder, _ordinal) = uleb128(remainder)?;
-                        let (remainder, _label) = map(
+                        let (remainder, ordinal) = uleb128(remainder)?;
+                        let (remainder, label) = map(
                             (take_till(|b| b == b'\x00'), tag("\x00")),
-                            |(s, _)| s,
+                            |(s, _)| BStr::new(s),
                         )
                         .parse(remainder)?;
 
+                        reexport_dylib = Some(ordinal);
+                        reexport_name = label.to_str().ok().map(str::to_string);
                         remaining_data = remainder;
                     }
-                    EXPORT_SYMBOL_FLAGS_WEAK_DEFINITION => {
-                        let (remainder, _offset) = uleb128(remainder)?;
+                    _ => {
+                        // Regular exports, including weak definitions, are
+                        // followed by the image-relative address.
+                        let (remainder, addr) = uleb128(remainder)?;
+                        address = Some(addr);
                         remaining_data = remainder;
                     }
-                    _ => {}
                 }
             }
 
@@ -1242,46 +1836,202 @@ impl<'a> MachOFile<'a> {
             }
 
             if length != 0 {
-                self.exports.push(export_node.prefix)
+                self.exports.push(Export {
+                    name: export_node.prefix,
+                    address,
+                    flags: export_flags,
+                    reexport_dylib,
+                    reexport_name,
+                    stub_offset,
+                    resolver_offset,
+                });
             }
         }
 
         Ok((&[], ()))
     }
 
-    /// Parser that parses the imports at the offsets defined within LC_DYLD_INFO and LC_DYLD_INFO_ONLY
+    /// Resolves a dylib ordinal, as used by the bind opcode stream, to the
+    /// name of the dylib parsed from the corresponding `LC_LOAD_DYLIB`.
+    /// Returns `None` for the special ordinals (self, main executable,
+    /// flat/weak lookup) and for ordinals with no matching dylib.
+    fn resolve_dylib_ordinal(&self, ordinal: i64) -> Option<String> {
+        let index = usize::try_from(ordinal).ok()?.checked_sub(1)?;
+        self.dylibs.get(index)?.name.to_str().ok().map(str::to_string)
+    }
+
+    /// Pushes one structured [`Bind`] entry built from the interpreter
+    /// state accumulated so far, and records its symbol in the flat
+    /// `imports` list for backward compatibility.
+    #[allow(clippy::too_many_arguments)]
+    fn emit_bind(
+        &mut self,
+        symbol: &str,
+        dylib_ordinal: i64,
+        segment_index: Option<u8>,
+        segment_offset: u64,
+        bind_type: u8,
+        addend: i64,
+        weak: bool,
+    ) {
+        let segment = segment_index.map(u32::from);
+        let address = segment_index
+            .and_then(|index| self.segments.get(index as usize))
+            .map(|segment| segment.vmaddr.wrapping_add(segment_offset));
+
+        self.imports.push(symbol.to_string());
+        self.binds.push(Bind {
+            symbol: symbol.to_string(),
+            library: self.resolve_dylib_ordinal(dylib_ordinal),
+            segment,
+            address,
+            bind_type,
+            addend,
+            weak,
+        });
+    }
+
+    /// Parser that parses the imports at the offsets defined within LC_DYLD_INFO and LC_DYLD_INFO_ONLY.
+    ///
+    /// This runs the full bind opcode interpreter, tracking the dylib
+    /// ordinal, segment+offset, bind type and addend as the opcode stream
+    /// is consumed, and emitting a structured [`Bind`] entry for every
+    /// pointer bound by a `DO_BIND*` opcode, in addition to the flat symbol
+    /// name kept in `imports`.
     fn parse_imports(&mut self, data: &'a [u8]) -> IResult<&'a [u8], ()> {
         let mut remainder: &[u8] = data;
         let mut entry: u8;
 
+        let mut dylib_ordinal: i64 = 0;
+        let mut segment_index: Option<u8> = None;
+        let mut segment_offset: u64 = 0;
+        let mut bind_type: u8 = 0;
+        let mut addend: i64 = 0;
+        let mut weak = false;
+        let mut symbol: Option<String> = None;
+
         while !remainder.is_empty() {
             (remainder, entry) = u8(remainder)?;
             let opcode = entry & BIND_OPCODE_MASK;
-            let _immediate = entry & BIND_IMMEDIATE_MASK;
+            let immediate = entry & BIND_IMMEDIATE_MASK;
             match opcode {
-                BIND_OPCODE_SET_DYLIB_ORDINAL_ULEB
-                | BIND_OPCODE_SET_SEGMENT_AND_OFFSET_ULEB
-                | BIND_OPCODE_ADD_ADDR_ULEB
-                | BIND_OPCODE_DO_BIND_ADD_ADDR_ULEB => {
-                    (remainder, _) = uleb128(remainder)?;
+                BIND_OPCODE_DONE => {
+                    break;
                 }
-                BIND_OPCODE_DO_BIND_ULEB_TIMES_SKIPPING_ULEB => {
-                    (remainder, _) = uleb128(remainder)?;
-                    (remainder, _) = uleb128(remainder)?;
+                BIND_OPCODE_SET_DYLIB_ORDINAL_IMM => {
+                    dylib_ordinal = immediate as i64;
                 }
-                BIND_OPCODE_SET_ADDEND_SLEB => {
-                    (remainder, _) = sleb128(remainder)?;
+                BIND_OPCODE_SET_DYLIB_ORDINAL_ULEB => {
+                    let ordinal;
+                    (remainder, ordinal) = uleb128(remainder)?;
+                    dylib_ordinal = ordinal as i64;
+                }
+                BIND_OPCODE_SET_DYLIB_SPECIAL_IMM => {
+                    // The immediate is a 4-bit two's complement special
+                    // ordinal (0 = self, -1 = main executable, -2 = flat
+                    // lookup, -3 = weak lookup).
+                    dylib_ordinal = (((immediate << 4) as i8) >> 4) as i64;
                 }
-
                 BIND_OPCODE_SET_SYMBOL_TRAILING_FLAGS_IMM => {
-                    let (import_remainder, strr) = map(
+                    let strr;
+                    (remainder, strr) = map(
                         (take_till(|b| b == b'\x00'), tag("\x00")),
                         |(s, _)| BStr::new(s),
                     )
                     .parse(remainder)?;
-                    remainder = import_remainder;
-                    if let Ok(import) = strr.to_str() {
-                        self.imports.push(import.to_string());
+                    weak = immediate & BIND_SYMBOL_FLAGS_WEAK_IMPORT != 0;
+                    symbol = strr.to_str().ok().map(str::to_string);
+                }
+                BIND_OPCODE_SET_TYPE_IMM => {
+                    bind_type = immediate;
+                }
+                BIND_OPCODE_SET_ADDEND_SLEB => {
+                    (remainder, addend) = sleb128(remainder)?;
+                }
+                BIND_OPCODE_SET_SEGMENT_AND_OFFSET_ULEB => {
+                    segment_index = Some(immediate);
+                    (remainder, segment_offset) = uleb128(remainder)?;
+                }
+                BIND_OPCODE_ADD_ADDR_ULEB => {
+                    let value;
+                    (remainder, value) = uleb128(remainder)?;
+                    segment_offset = segment_offset.wrapping_add(value);
+                }
+                BIND_OPCODE_DO_BIND => {
+                    if let Some(symbol) = symbol.as_deref() {
+                        self.emit_bind(
+                            symbol,
+                            dylib_ordinal,
+                            segment_index,
+                            segment_offset,
+                            bind_type,
+                            addend,
+                            weak,
+                        );
+                    }
+                    segment_offset =
+                        segment_offset.wrapping_add(self.pointer_size());
+                }
+                BIND_OPCODE_DO_BIND_ADD_ADDR_ULEB => {
+                    if let Some(symbol) = symbol.as_deref() {
+                        self.emit_bind(
+                            symbol,
+                            dylib_ordinal,
+                            segment_index,
+                            segment_offset,
+                            bind_type,
+                            addend,
+                            weak,
+                        );
+                    }
+                    let value;
+                    (remainder, value) = uleb128(remainder)?;
+                    segment_offset = segment_offset
+                        .wrapping_add(self.pointer_size())
+                        .wrapping_add(value);
+                }
+                BIND_OPCODE_DO_BIND_ADD_ADDR_IMM_SCALED => {
+                    if let Some(symbol) = symbol.as_deref() {
+                        self.emit_bind(
+                            symbol,
+                            dylib_ordinal,
+                            segment_index,
+                            segment_offset,
+                            bind_type,
+                            addend,
+                            weak,
+                        );
+                    }
+                    segment_offset = segment_offset
+                        .wrapping_add(self.pointer_size())
+                        .wrapping_add(
+                            (immediate as u64)
+                                .wrapping_mul(self.pointer_size()),
+                        );
+                }
+                BIND_OPCODE_DO_BIND_ULEB_TIMES_SKIPPING_ULEB => {
+                    let count;
+                    let skip;
+                    (remainder, count) = uleb128(remainder)?;
+                    (remainder, skip) = uleb128(remainder)?;
+                    // `count` comes straight from the file being scanned;
+                    // clamp it so a crafted value doesn't hang the scanner.
+                    let count = count.min(MAX_BIND_ULEB_TIMES_COUNT);
+                    for _ in 0..count {
+                        if let Some(symbol) = symbol.as_deref() {
+                            self.emit_bind(
+                                symbol,
+                                dylib_ordinal,
+                                segment_index,
+                                segment_offset,
+                                bind_type,
+                                addend,
+                                weak,
+                            );
+                        }
+                        segment_offset = segment_offset
+                            .wrapping_add(self.pointer_size())
+                            .wrapping_add(skip);
                     }
                 }
                 _ => {}
@@ -1317,50 +2067,225 @@ impl<'a> MachOFile<'a> {
             )| {
                 ChainedFixupsHeader {
                     _fixups_version: fixups_version,
-                    _starts_offset: starts_offset,
+                    starts_offset,
                     imports_offset,
                     symbols_offset,
                     imports_count,
-                    _imports_format: imports_format,
+                    imports_format,
                     _symbols_format: symbols_format,
                 }
             },
         )
     }
 
+    /// Parser that parses a `dyld_chained_starts_in_segment` structure,
+    /// found at each non-zero offset listed by `dyld_chained_starts_in_image`.
+    fn chained_starts_in_segment(
+        &self,
+    ) -> impl Parser<
+        &'a [u8],
+        Output = ChainedStartsInSegment,
+        Error = NomError<'a>,
+    > + '_ {
+        move |input: &'a [u8]| {
+            let (
+                remainder,
+                (
+                    _size,
+                    page_size,
+                    pointer_format,
+                    segment_offset,
+                    _max_valid_pointer,
+                    page_count,
+                ),
+            ) = (
+                u32(self.endianness), // size
+                u16(self.endianness), // page_size
+                u16(self.endianness), // pointer_format
+                u64(self.endianness), // segment_offset
+                u32(self.endianness), // max_valid_pointer
+                u16(self.endianness), // page_count
+            )
+                .parse(input)?;
+
+            let (remainder, page_start) =
+                count(u16(self.endianness), page_count as usize)
+                    .parse(remainder)?;
+
+            Ok((
+                remainder,
+                ChainedStartsInSegment {
+                    page_size,
+                    pointer_format,
+                    segment_offset,
+                    page_start,
+                },
+            ))
+        }
+    }
+
+    /// Resolves the symbol name for the import at `ordinal` in the chained
+    /// fixups imports table described by `header`.
+    fn resolve_chained_import(
+        &self,
+        data: &'a [u8],
+        header: &ChainedFixupsHeader,
+        ordinal: u32,
+    ) -> Option<String> {
+        if ordinal >= header.imports_count {
+            return None;
+        }
+
+        // The entry layout (and therefore its size) depends on
+        // `imports_format`: a plain `dyld_chained_import` is 4 bytes, a
+        // `dyld_chained_import_addend` adds a 4-byte addend, and a
+        // `dyld_chained_import_addend64` widens everything to 8 bytes.
+        let entry_size: usize = match header.imports_format {
+            DYLD_CHAINED_IMPORT => 4,
+            DYLD_CHAINED_IMPORT_ADDEND => 8,
+            DYLD_CHAINED_IMPORT_ADDEND64 => 16,
+            _ => return None,
+        };
+
+        let entry_offset = (header.imports_offset as usize)
+            .saturating_add(ordinal as usize * entry_size);
+
+        let entry =
+            data.get(entry_offset..entry_offset.saturating_add(entry_size))?;
+
+        let name_offset = if header.imports_format
+            == DYLD_CHAINED_IMPORT_ADDEND64
+        {
+            let (_, first_word) = u64(self.endianness)(entry).ok()?;
+            (first_word >> 32) as u32
+        } else {
+            let (_, first_word) = u32(self.endianness)(entry).ok()?;
+            first_word >> 9
+        };
+
+        let name_buffer = data.get(
+            (header.symbols_offset as usize)
+                .saturating_add(name_offset as usize)..,
+        )?;
+
+        let (_, name) =
+            map((take_till(|b| b == b'\x00'), tag("\x00")), |(s, _)| s)
+                .parse(name_buffer)
+                .ok()?;
+
+        name.to_str().ok().map(|s| s.to_string())
+    }
+
+    /// Walks every chained pointer in `starts`, pushing the resolved import
+    /// name for each bound pointer found into `self.imports`.
+    fn walk_chained_starts(
+        &mut self,
+        data: &'a [u8],
+        header: &ChainedFixupsHeader,
+        starts: &ChainedStartsInSegment,
+    ) {
+        // `next` is expressed in units of 4 bytes for the non-authenticated
+        // pointer formats (which can pack pointers tightly on 32-bit
+        // targets) and in units of 8 bytes for the arm64e formats.
+        let stride: u64 = match starts.pointer_format {
+            DYLD_CHAINED_PTR_64 | DYLD_CHAINED_PTR_64_OFFSET => 4,
+            _ => 8,
+        };
+
+        for (page_index, &page_start) in starts.page_start.iter().enumerate()
+        {
+            if page_start == DYLD_CHAINED_PTR_START_NONE {
+                continue;
+            }
+
+            let mut offset = starts
+                .segment_offset
+                .saturating_add((page_index as u64) * starts.page_size as u64)
+                .saturating_add(page_start as u64);
+
+            let mut visited = HashSet::<u64>::new();
+
+            while visited.insert(offset) {
+                let Some((_, raw)) = data
+                    .get(offset as usize..offset.saturating_add(8) as usize)
+                    .and_then(|b| u64(self.endianness)(b).ok())
+                else {
+                    break;
+                };
+
+                // The non-authenticated formats keep the bind flag in bit 63
+                // and a 12-bit `next`; the arm64e formats use bit 63 for the
+                // auth flag, keep the bind flag in bit 62 and an 11-bit `next`.
+                let (bind, next) = match starts.pointer_format {
+                    DYLD_CHAINED_PTR_64 | DYLD_CHAINED_PTR_64_OFFSET => {
+                        ((raw >> 63) & 1 != 0, (raw >> 51) & 0xfff)
+                    }
+                    _ => ((raw >> 62) & 1 != 0, (raw >> 51) & 0x7ff),
+                };
+
+                if bind {
+                    let ordinal = match starts.pointer_format {
+                        DYLD_CHAINED_PTR_ARM64E_USERLAND24 => {
+                            (raw & 0x00ff_ffff) as u32
+                        }
+                        DYLD_CHAINED_PTR_ARM64E
+                        | DYLD_CHAINED_PTR_ARM64E_USERLAND => {
+                            (raw & 0xffff) as u32
+                        }
+                        _ => (raw & 0x00ff_ffff) as u32,
+                    };
+
+                    if let Some(name) =
+                        self.resolve_chained_import(data, header, ordinal)
+                    {
+                        self.imports.push(name);
+                    }
+                }
+
+                if next == 0 {
+                    break;
+                }
+
+                offset = offset.saturating_add(next * stride);
+            }
+        }
+    }
+
     /// Parser that parses the chained fixup imports designated by LC_DYLD_CHAINED_FIXUPS.
+    ///
+    /// Walks `dyld_chained_starts_in_image` and, for each segment it lists,
+    /// `dyld_chained_starts_in_segment`, following the chained pointer chain
+    /// on every bound page to recover the imports used by binaries built
+    /// with chained fixups (iOS 14+ / arm64e, and most recent macOS apps),
+    /// which don't have a legacy LC_DYLD_INFO bind opcode stream to parse.
     fn parse_chained_fixups(
         &mut self,
         data: &'a [u8],
     ) -> IResult<&'a [u8], ()> {
         let (_, header) = self.chained_fixup_header().parse(data)?;
 
-        if let Some(import_data) = data.get(header.imports_offset as usize..) {
-            let mut remainder = import_data;
-            let mut chained_import_value: u32;
-
-            for _ in 0..header.imports_count {
-                (remainder, chained_import_value) =
-                    u32(self.endianness)(remainder)?;
+        if let Some(starts_data) = data.get(header.starts_offset as usize..) {
+            let (remainder, seg_count) = u32(self.endianness)(starts_data)?;
+            let (_, seg_info_offsets) =
+                count(u32(self.endianness), seg_count as usize)
+                    .parse(remainder)?;
 
-                let _lib_ordinal = chained_import_value & 0xff;
-                let _import_kind = (chained_import_value >> 8) & 0x1;
-                let name_offset = chained_import_value >> 9;
+            for seg_offset in
+                seg_info_offsets.into_iter().filter(|offset| *offset != 0)
+            {
+                let Some(seg_data) =
+                    starts_data.get(seg_offset as usize..)
+                else {
+                    continue;
+                };
 
-                if let Some(name_buffer) = data.get(
-                    header.symbols_offset.saturating_add(name_offset)
-                        as usize..,
-                ) {
-                    let (_remainder, import_str) = map(
-                        (take_till(|b| b == b'\x00'), tag("\x00")),
-                        |(s, _)| s,
-                    )
-                    .parse(name_buffer)?;
+                let Ok((_, starts)) =
+                    self.chained_starts_in_segment().parse(seg_data)
+                else {
+                    continue;
+                };
 
-                    if let Ok(import) = import_str.to_str() {
-                        self.imports.push(import.to_string());
-                    }
-                }
+                self.walk_chained_starts(data, &header, &starts);
             }
         }
 
@@ -1636,6 +2561,61 @@ impl<'a> MachOFile<'a> {
     }
 }
 
+/// Iterates over the load commands of a Mach-O file, yielding the command
+/// type, its declared size, and the bytes that follow its 8-byte header,
+/// without interpreting the command itself. Cf. the iterator of the same
+/// name in the `object` crate.
+///
+/// Bounds are validated as the iterator advances: a `cmdsize` smaller than
+/// the 8-byte header it must contain is rejected, and the iterator never
+/// yields data beyond `sizeofcmds` bytes from the start, even if a
+/// corrupted `cmdsize` claims otherwise.
+pub struct LoadCommandIterator<'a> {
+    data: &'a [u8],
+    endianness: Endianness,
+}
+
+impl<'a> LoadCommandIterator<'a> {
+    /// Creates an iterator over the load commands starting at `data`,
+    /// clamped to `sizeofcmds` bytes as reported by the Mach-O header.
+    pub fn new(
+        data: &'a [u8],
+        endianness: Endianness,
+        sizeofcmds: u32,
+    ) -> Self {
+        let len = (sizeofcmds as usize).min(data.len());
+        Self { data: &data[..len], endianness }
+    }
+}
+
+impl<'a> Iterator for LoadCommandIterator<'a> {
+    /// `(cmd, cmdsize, data)`, where `data` is the `cmdsize - 8` bytes that
+    /// follow the command's type and size fields.
+    type Item = (u32, u32, &'a [u8]);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (remainder, (cmd, cmdsize)) =
+            (u32(self.endianness), u32(self.endianness))
+                .parse(self.data)
+                .ok()?;
+
+        // A command must be at least as large as the two fields that
+        // describe it.
+        if cmdsize < 8 {
+            return None;
+        }
+
+        let data_size = (cmdsize - 8) as usize;
+        let cmd_data = remainder.get(..data_size)?;
+
+        // If `cmdsize` claims more bytes than are actually left, stop
+        // instead of overrunning into whatever follows `sizeofcmds`.
+        self.data = remainder.get(data_size..)?;
+
+        Some((cmd, cmdsize, cmd_data))
+    }
+}
+
 struct FatArch {
     cputype: u32,
     cpusubtype: u32,
@@ -1682,6 +2662,20 @@ struct Section<'a> {
     reserved1: u32,
     reserved2: u32,
     reserved3: Option<u32>, // Only set in 64-bits binaries
+    relocations: Vec<Relocation>,
+}
+
+/// A decoded Mach-O relocation entry, as referenced by a section's `reloff`
+/// and `nreloc` fields.
+struct Relocation {
+    r_address: i32,
+    r_symbolnum: u32,
+    r_pcrel: bool,
+    r_length: u32,
+    r_extern: bool,
+    r_type: u32,
+    scattered: bool,
+    r_value: Option<i32>,
 }
 
 struct Dylib<'a> {
@@ -1691,6 +2685,29 @@ struct Dylib<'a> {
     compatibility_version: u32,
 }
 
+/// A structured bind opcode-stream entry, emitted once per bound pointer
+/// (a single `DO_BIND*` opcode can bind several) while interpreting the LC_DYLD_INFO / LC_DYLD_INFO_ONLY bind
+/// stream. Unlike `imports`, which only records bound symbol names, this
+/// keeps enough interpreter state to tell, for instance, a symbol imported
+/// from one library from the same symbol imported from another.
+struct Bind {
+    symbol: String,
+    /// Name of the dylib the symbol is bound to, resolved from the dylib
+    /// ordinal through the already-parsed `LC_LOAD_DYLIB` list. `None` for
+    /// special ordinals (self, main executable, flat/weak lookup) or when
+    /// the ordinal doesn't match any parsed dylib.
+    library: Option<String>,
+    /// Index, within `segments`, of the segment the bind applies to.
+    /// `None` if no `BIND_OPCODE_SET_SEGMENT_AND_OFFSET_ULEB` was seen yet.
+    segment: Option<u32>,
+    /// Address of the bind, computed as the segment's `vmaddr` plus the
+    /// running offset. `None` when `segment` is `None`.
+    address: Option<u64>,
+    bind_type: u8,
+    addend: i64,
+    weak: bool,
+}
+
 #[derive(Default)]
 struct Certificate {
     issuer: String,
@@ -1786,14 +2803,23 @@ struct DyldChainFixups {
 
 struct ChainedFixupsHeader {
     _fixups_version: u32,
-    _starts_offset: u32,
+    starts_offset: u32,
     imports_offset: u32,
     symbols_offset: u32,
     imports_count: u32,
-    _imports_format: u32,
+    imports_format: u32,
     _symbols_format: u32,
 }
 
+/// `dyld_chained_starts_in_segment`, describing which pages of a segment
+/// contain chained pointer fixups and where each page's chain starts.
+struct ChainedStartsInSegment {
+    page_size: u16,
+    pointer_format: u16,
+    segment_offset: u64,
+    page_start: Vec<u16>,
+}
+
 struct BuildVersionCommand {
     platform: u32,
     minos: u32,
@@ -1817,6 +2843,29 @@ struct ExportNode {
     prefix: String,
 }
 
+/// A structured entry from the dyld export trie (`LC_DYLD_INFO`,
+/// `LC_DYLD_INFO_ONLY`, or `LC_DYLD_EXPORTS_TRIE`), built from a terminal
+/// node's name prefix and its decoded payload.
+struct Export {
+    name: String,
+    /// Image-relative address of the export. `None` for re-exports, which
+    /// have no address of their own.
+    address: Option<u64>,
+    flags: u64,
+    /// Ordinal of the dylib this symbol is re-exported from, set when
+    /// `flags` is `EXPORT_SYMBOL_FLAGS_REEXPORT`.
+    reexport_dylib: Option<u64>,
+    /// Name of the re-exported symbol in the target dylib, which can
+    /// differ from `name` when the re-export renames the symbol.
+    reexport_name: Option<String>,
+    /// Offset of the resolver stub, set when `flags` is
+    /// `EXPORT_SYMBOL_FLAGS_STUB_AND_RESOLVER`.
+    stub_offset: Option<u64>,
+    /// Offset of the resolver function, set when `flags` is
+    /// `EXPORT_SYMBOL_FLAGS_STUB_AND_RESOLVER`.
+    resolver_offset: Option<u64>,
+}
+
 /// Parser that reads a 32-bits or 64-bits
 fn uint(
     endianness: Endianness,
@@ -1832,6 +2881,55 @@ fn uint(
     }
 }
 
+/// Parser that reads a single 8-byte Mach-O relocation entry. A "scattered"
+/// relocation is identified by the top bit of the first word
+/// (`R_SCATTERED`) and packs its bitfields differently from a "generic"
+/// one, which additionally carries a target symbol table index.
+fn relocation(
+    endianness: Endianness,
+) -> impl FnMut(&[u8]) -> IResult<&[u8], Relocation> {
+    move |input: &[u8]| {
+        let (remainder, word0) = u32(endianness)(input)?;
+
+        if word0 & R_SCATTERED != 0 {
+            let (remainder, r_value) = i32(endianness)(remainder)?;
+            Ok((
+                remainder,
+                Relocation {
+                    r_address: (word0 & 0x00ff_ffff) as i32,
+                    r_symbolnum: 0,
+                    r_pcrel: word0 & 0x4000_0000 != 0,
+                    r_length: (word0 >> 28) & 0x3,
+                    r_extern: false,
+                    r_type: (word0 >> 24) & 0xf,
+                    scattered: true,
+                    r_value: Some(r_value),
+                },
+            ))
+        } else {
+            let (remainder, word1) = u32(endianness)(remainder)?;
+            Ok((
+                remainder,
+                Relocation {
+                    r_address: word0 as i32,
+                    r_symbolnum: word1 & 0x00ff_ffff,
+                    r_pcrel: word1 & 0x0100_0000 != 0,
+                    r_length: (word1 >> 25) & 0x3,
+                    r_extern: word1 & 0x0800_0000 != 0,
+                    r_type: (word1 >> 28) & 0xf,
+                    scattered: false,
+                    r_value: None,
+                },
+            ))
+        }
+    }
+}
+
+/// Formats `bytes` as a lowercase hex string, as used for `cdhash`.
+fn to_hex_string(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
 /// Parser that reads [ULEB128][1].
 ///
 /// Notice however that this function returns a `u64`, so it's able to parse
@@ -1995,132 +3093,159 @@ impl From<MachO<'_>> for protos::macho::Macho {
         // at the top level of `protos::macho::Macho` structure. If it is a
         // multi-architecture binary (FAT binary) then fill the `fat_arch`
         // and `file` arrays.
-        if macho.files.len() == 1 {
-            let m = macho.files.first().unwrap();
-            result.set_magic(m.header.magic);
-            result.set_ncmds(m.header.ncmds);
-            result.set_cputype(m.header.cputype);
-            result.set_cpusubtype(m.header.cpusubtype);
-            result.set_filetype(m.header.filetype);
-            result.set_flags(m.header.flags);
-            result.set_sizeofcmds(m.header.sizeofcmds);
-            result.reserved = m.header.reserved;
-            result.entry_point = m.entry_point_offset;
-            result.stack_size = m.stack_size;
-            m.source_version.clone_into(&mut result.source_version);
-            result.dynamic_linker = m.dynamic_linker.map(|dl| dl.into());
-
-            if let Some(symtab) = &m.symtab {
-                result.symtab = MessageField::some(symtab.into());
-            }
+        if macho.fat_magic.is_none() {
+            if let Some(SingleArch::MachO(m)) = macho.files.first() {
+                result.set_magic(m.header.magic);
+                result.set_ncmds(m.header.ncmds);
+                result.set_cputype(m.header.cputype);
+                result.set_cpusubtype(m.header.cpusubtype);
+                result.set_filetype(m.header.filetype);
+                result.set_flags(m.header.flags);
+                result.set_sizeofcmds(m.header.sizeofcmds);
+                result.reserved = m.header.reserved;
+                result.entry_point = m.entry_point_offset;
+                result.stack_size = m.stack_size;
+                m.source_version.clone_into(&mut result.source_version);
+                result.dynamic_linker = m.dynamic_linker.map(|dl| dl.into());
 
-            if let Some(dysymtab) = &m.dysymtab {
-                result.dysymtab = MessageField::some(dysymtab.into());
-            }
+                if let Some(symtab) = &m.symtab {
+                    result.symtab = Some(symtab.into());
+                }
 
-            if let Some(cs_data) = &m.code_signature_data {
-                result.code_signature_data =
-                    MessageField::some(cs_data.into());
-            }
+                if let Some(dysymtab) = &m.dysymtab {
+                    result.dysymtab = Some(dysymtab.into());
+                }
 
-            if let Some(dyld_info) = &m.dyld_info {
-                result.dyld_info = MessageField::some(dyld_info.into());
-            };
+                if let Some(cs_data) = &m.code_signature_data {
+                    result.code_signature_data =
+                        Some(cs_data.into());
+                }
 
-            if let Some(uuid) = &m.uuid {
-                let mut uuid_str = String::new();
+                if let Some(dyld_info) = &m.dyld_info {
+                    result.dyld_info = Some(dyld_info.into());
+                };
 
-                for (idx, c) in uuid.iter().enumerate() {
-                    match idx {
-                        3 | 5 | 7 | 9 => {
-                            uuid_str.push_str(format!("{c:02X}").as_str());
-                            uuid_str.push('-');
-                        }
-                        _ => {
-                            uuid_str.push_str(format!("{c:02X}").as_str());
+                if let Some(uuid) = &m.uuid {
+                    let mut uuid_str = String::new();
+
+                    for (idx, c) in uuid.iter().enumerate() {
+                        match idx {
+                            3 | 5 | 7 | 9 => {
+                                uuid_str.push_str(format!("{c:02X}").as_str());
+                                uuid_str.push('-');
+                            }
+                            _ => {
+                                uuid_str.push_str(format!("{c:02X}").as_str());
+                            }
                         }
                     }
-                }
 
-                result.uuid = Some(uuid_str.clone());
-            }
+                    result.uuid = Some(uuid_str.clone());
+                }
 
-            if let Some(bv) = &m.build_version {
-                result.build_version = MessageField::some(bv.into());
-            }
+                if let Some(bv) = &m.build_version {
+                    result.build_version = Some(bv.into());
+                }
 
-            if let Some(mv) = &m.min_version {
-                result.min_version = MessageField::some(mv.into());
-            }
+                if let Some(mv) = &m.min_version {
+                    result.min_version = Some(mv.into());
+                }
 
-            result.segments.extend(m.segments.iter().map(|seg| seg.into()));
-            result.dylibs.extend(m.dylibs.iter().map(|dylib| dylib.into()));
-            result
-                .rpaths
-                .extend(m.rpaths.iter().map(|rpath: &&[u8]| rpath.to_vec()));
-            result.entitlements.extend(m.entitlements.clone());
-            result.exports.extend(m.exports.clone());
-            result.imports.extend(m.imports.clone());
-
-            // If the exports are empty, iterate the symbol table entries to
-            // check like dyld_info does:
-            // https://github.com/apple-oss-distributions/dyld/blob/main/other-tools/dyld_info.cpp#L560-L617
-            if m.dyld_export_trie.is_none() && m.dyld_info.is_none() {
-                if let Some(symtab) = &m.symtab {
-                    result.exports.extend(symtab.entries.iter().filter_map(
-                        |e| {
-                            let t = e.tags & N_TYPE;
-
-                            if (e.tags & N_EXT != 0)
-                                && ((t == N_SECT)
-                                    || (t == N_ABS)
-                                    || (t == N_INDR))
-                                && ((e.tags & N_STAB) == 0)
-                            {
-                                Some(BStr::new(e.value).to_string())
-                            } else {
-                                None
-                            }
-                        },
-                    ))
+                result.segments.extend(m.segments.iter().map(|seg| seg.into()));
+                result.dylibs.extend(m.dylibs.iter().map(|dylib| dylib.into()));
+                result
+                    .rpaths
+                    .extend(m.rpaths.iter().map(|rpath: &&[u8]| rpath.to_vec()));
+                result.entitlements.extend(m.entitlements.clone());
+                result.exports.extend(m.exports.iter().map(|export| export.into()));
+                result.imports.extend(m.imports.clone());
+                result.identifier = m.identifier.clone();
+                result.team_id = m.team_id.clone();
+                result.cdhash = m.cdhash.as_deref().map(to_hex_string);
+                result.hash_type = m.hash_type.map(u32::from);
+                result.cs_flags = m.cs_flags;
+                result.binds.extend(m.binds.iter().map(|bind| bind.into()));
+
+                // If the exports are empty, iterate the symbol table entries to
+                // check like dyld_info does:
+                // https://github.com/apple-oss-distributions/dyld/blob/main/other-tools/dyld_info.cpp#L560-L617
+                if m.dyld_export_trie.is_none() && m.dyld_info.is_none() {
+                    if let Some(symtab) = &m.symtab {
+                        result.exports.extend(symtab.entries.iter().filter_map(
+                            |e| {
+                                let t = e.tags & N_TYPE;
+
+                                if (e.tags & N_EXT != 0)
+                                    && ((t == N_SECT)
+                                        || (t == N_ABS)
+                                        || (t == N_INDR))
+                                    && ((e.tags & N_STAB) == 0)
+                                {
+                                    let mut export =
+                                        protos::macho::Export::new();
+                                    export.set_name(
+                                        BStr::new(e.value).to_string(),
+                                    );
+                                    Some(export)
+                                } else {
+                                    None
+                                }
+                            },
+                        ))
+                    }
                 }
-            }
 
-            // If the imports are empty, iterate the symbol table entries to
-            // check for undefined symbols like dyld_info does:
-            // https://github.com/apple-oss-distributions/dyld/blob/main/other-tools/dyld_info.cpp#L372-L398
-            if m.dyld_chain_fixups.is_none() && m.dyld_info.is_none() {
-                if let Some(symtab) = &m.symtab {
-                    result.imports.extend(symtab.entries.iter().filter_map(
-                        |e| {
-                            let t = e.tags & N_TYPE;
-
-                            if (t == N_UNDF) && (e.tags & N_STAB == 0) {
-                                Some(BStr::new(e.value).to_string())
-                            } else {
-                                None
-                            }
-                        },
-                    ))
+                // If the imports are empty, iterate the symbol table entries to
+                // check for undefined symbols like dyld_info does:
+                // https://github.com/apple-oss-distributions/dyld/blob/main/other-tools/dyld_info.cpp#L372-L398
+                if m.dyld_chain_fixups.is_none() && m.dyld_info.is_none() {
+                    if let Some(symtab) = &m.symtab {
+                        result.imports.extend(symtab.entries.iter().filter_map(
+                            |e| {
+                                let t = e.tags & N_TYPE;
+
+                                if (t == N_UNDF) && (e.tags & N_STAB == 0) {
+                                    Some(BStr::new(e.value).to_string())
+                                } else {
+                                    None
+                                }
+                            },
+                        ))
+                    }
                 }
-            }
 
-            result
-                .certificates
-                .extend(m.certificates.iter().map(|cert| cert.into()));
+                result
+                    .certificates
+                    .extend(m.certificates.iter().map(|cert| cert.into()));
 
-            result
-                .set_number_of_segments(m.segments.len().try_into().unwrap());
+                result.set_number_of_segments(
+                    m.segments.len().try_into().unwrap(),
+                );
 
-            result
-                .linker_options
-                .extend(m.linker_options.iter().map(|lo| lo.to_vec()));
+                result
+                    .linker_options
+                    .extend(m.linker_options.iter().map(|lo| lo.to_vec()));
+            }
         } else {
             result.fat_magic = macho.fat_magic;
             result.set_nfat_arch(macho.archs.len().try_into().unwrap());
             result.fat_arch.extend(macho.archs.iter().map(|arch| arch.into()));
-            result.file.extend(macho.files.iter().map(|file| file.into()));
+            result.file.extend(
+                macho
+                    .files
+                    .iter()
+                    .flat_map(|f| match f {
+                        SingleArch::MachO(m) => vec![m],
+                        SingleArch::Archive(members) => {
+                            members.iter().collect()
+                        }
+                    })
+                    .map(|file| file.into()),
+            );
         }
+
+        result.images.extend(macho.images.iter().map(|file| file.into()));
+
         result
     }
 }
@@ -2142,19 +3267,19 @@ impl From<&MachOFile<'_>> for protos::macho::File {
         result.dynamic_linker = macho.dynamic_linker.map(|dl| dl.into());
 
         if let Some(symtab) = &macho.symtab {
-            result.symtab = MessageField::some(symtab.into());
+            result.symtab = Some(symtab.into());
         }
 
         if let Some(dysymtab) = &macho.dysymtab {
-            result.dysymtab = MessageField::some(dysymtab.into());
+            result.dysymtab = Some(dysymtab.into());
         }
 
         if let Some(cs_data) = &macho.code_signature_data {
-            result.code_signature_data = MessageField::some(cs_data.into());
+            result.code_signature_data = Some(cs_data.into());
         }
 
         if let Some(dyld_info) = &macho.dyld_info {
-            result.dyld_info = MessageField::some(dyld_info.into());
+            result.dyld_info = Some(dyld_info.into());
         };
 
         if let Some(uuid) = &macho.uuid {
@@ -2176,19 +3301,25 @@ impl From<&MachOFile<'_>> for protos::macho::File {
         }
 
         if let Some(bv) = &macho.build_version {
-            result.build_version = MessageField::some(bv.into());
+            result.build_version = Some(bv.into());
         }
 
         if let Some(mv) = &macho.min_version {
-            result.min_version = MessageField::some(mv.into());
+            result.min_version = Some(mv.into());
         }
 
         result.segments.extend(macho.segments.iter().map(|seg| seg.into()));
         result.dylibs.extend(macho.dylibs.iter().map(|dylib| dylib.into()));
         result.rpaths.extend(macho.rpaths.iter().map(|rpath| rpath.to_vec()));
         result.entitlements.extend(macho.entitlements.clone());
-        result.exports.extend(macho.exports.clone());
+        result.exports.extend(macho.exports.iter().map(|export| export.into()));
         result.imports.extend(macho.imports.clone());
+        result.identifier = macho.identifier.clone();
+        result.team_id = macho.team_id.clone();
+        result.cdhash = macho.cdhash.as_deref().map(to_hex_string);
+        result.hash_type = macho.hash_type.map(u32::from);
+        result.cs_flags = macho.cs_flags;
+        result.binds.extend(macho.binds.iter().map(|bind| bind.into()));
 
         // If the exports are empty, iterate the symbol table entries to check
         // like dyld_info does:
@@ -2202,7 +3333,9 @@ impl From<&MachOFile<'_>> for protos::macho::File {
                         && ((t == N_SECT) || (t == N_ABS) || (t == N_INDR))
                         && ((e.tags & N_STAB) == 0)
                     {
-                        Some(BStr::new(e.value).to_string())
+                        let mut export = protos::macho::Export::new();
+                        export.set_name(BStr::new(e.value).to_string());
+                        Some(export)
                     } else {
                         None
                     }
@@ -2286,6 +3419,52 @@ impl From<&Section<'_>> for protos::macho::Section {
         result.set_reserved1(sec.reserved1);
         result.set_reserved2(sec.reserved2);
         result.reserved3 = sec.reserved3;
+        result
+            .relocations
+            .extend(sec.relocations.iter().map(|reloc| reloc.into()));
+        result
+    }
+}
+
+impl From<&Relocation> for protos::macho::Relocation {
+    fn from(reloc: &Relocation) -> Self {
+        let mut result = protos::macho::Relocation::new();
+        result.set_r_address(reloc.r_address);
+        result.set_r_symbolnum(reloc.r_symbolnum);
+        result.set_r_pcrel(reloc.r_pcrel);
+        result.set_r_length(reloc.r_length);
+        result.set_r_extern(reloc.r_extern);
+        result.set_r_type(reloc.r_type);
+        result.set_scattered(reloc.scattered);
+        result.r_value = reloc.r_value;
+        result
+    }
+}
+
+impl From<&Bind> for protos::macho::Bind {
+    fn from(bind: &Bind) -> Self {
+        let mut result = protos::macho::Bind::new();
+        result.set_symbol(bind.symbol.clone());
+        result.library = bind.library.clone();
+        result.segment = bind.segment;
+        result.address = bind.address;
+        result.set_type(bind.bind_type.into());
+        result.set_addend(bind.addend);
+        result.set_weak(bind.weak);
+        result
+    }
+}
+
+impl From<&Export> for protos::macho::Export {
+    fn from(export: &Export) -> Self {
+        let mut result = protos::macho::Export::new();
+        result.set_name(export.name.clone());
+        result.address = export.address;
+        result.set_flags(export.flags);
+        result.reexport_dylib = export.reexport_dylib;
+        result.reexport_name = export.reexport_name.clone();
+        result.stub_offset = export.stub_offset;
+        result.resolver_offset = export.resolver_offset;
         result
     }
 }
@@ -2404,12 +3583,7 @@ impl From<&MinVersion> for protos::macho::MinVersion {
     fn from(mv: &MinVersion) -> Self {
         let mut result = protos::macho::MinVersion::new();
 
-        result.set_device(
-            protobuf::EnumOrUnknown::<protos::macho::DeviceType>::from_i32(
-                mv.device as i32,
-            )
-            .unwrap(),
-        );
+        result.set_device(mv.device);
         result.set_version(convert_to_version_string(mv.version));
         result.set_sdk(convert_to_version_string(mv.sdk));
         result
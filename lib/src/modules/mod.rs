@@ -0,0 +1,8 @@
+//! Parsers for file formats exposed to YARA rules as modules.
+//!
+//! Only `macho` (and the `protos`/`utils` support code it depends on) is
+//! part of this snapshot.
+
+pub(crate) mod macho;
+pub(crate) mod protos;
+pub(crate) mod utils;
@@ -0,0 +1,671 @@
+//! Hand-written stand-ins for the `protos::macho` message types.
+//!
+//! In the real codebase these types are generated at build time by
+//! `protobuf-codegen` from a `macho.proto` schema; neither the schema nor
+//! the codegen pipeline is part of this pruned snapshot, so `parser.rs`'s
+//! `From` impls had no types to target at all. These structs reproduce
+//! just the field/setter surface `parser.rs` actually uses (plain owned
+//! fields instead of the real crate's `protobuf::MessageField`/
+//! `protobuf::EnumOrUnknown` wrappers) so that module compiles; they
+//! should be replaced by the genuine generated code once the `.proto`
+//! sources and build-time codegen are restored.
+
+#[derive(Default, Debug, Clone)]
+pub(crate) struct FatArch {
+    pub cputype: u32,
+    pub cpusubtype: u32,
+    pub offset: u64,
+    pub size: u64,
+    pub align: u32,
+    pub reserved: u32,
+}
+
+impl FatArch {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn set_cputype(&mut self, v: u32) {
+        self.cputype = v;
+    }
+    pub fn set_cpusubtype(&mut self, v: u32) {
+        self.cpusubtype = v;
+    }
+    pub fn set_offset(&mut self, v: u64) {
+        self.offset = v;
+    }
+    pub fn set_size(&mut self, v: u64) {
+        self.size = v;
+    }
+    pub fn set_align(&mut self, v: u32) {
+        self.align = v;
+    }
+    pub fn set_reserved(&mut self, v: u32) {
+        self.reserved = v;
+    }
+}
+
+#[derive(Default, Debug, Clone)]
+pub(crate) struct Relocation {
+    pub r_address: i32,
+    pub r_symbolnum: u32,
+    pub r_pcrel: bool,
+    pub r_length: u32,
+    pub r_extern: bool,
+    pub r_type: u32,
+    pub scattered: bool,
+    pub r_value: Option<i32>,
+}
+
+impl Relocation {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn set_r_address(&mut self, v: i32) {
+        self.r_address = v;
+    }
+    pub fn set_r_symbolnum(&mut self, v: u32) {
+        self.r_symbolnum = v;
+    }
+    pub fn set_r_pcrel(&mut self, v: bool) {
+        self.r_pcrel = v;
+    }
+    pub fn set_r_length(&mut self, v: u32) {
+        self.r_length = v;
+    }
+    pub fn set_r_extern(&mut self, v: bool) {
+        self.r_extern = v;
+    }
+    pub fn set_r_type(&mut self, v: u32) {
+        self.r_type = v;
+    }
+    pub fn set_scattered(&mut self, v: bool) {
+        self.scattered = v;
+    }
+}
+
+#[derive(Default, Debug, Clone)]
+pub(crate) struct Section {
+    pub segname: Vec<u8>,
+    pub sectname: Vec<u8>,
+    pub addr: u64,
+    pub size: u64,
+    pub offset: u32,
+    pub align: u32,
+    pub reloff: u32,
+    pub nreloc: u32,
+    pub flags: u32,
+    pub reserved1: u32,
+    pub reserved2: u32,
+    pub reserved3: Option<u32>,
+    pub relocations: Vec<Relocation>,
+}
+
+impl Section {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn set_segname(&mut self, v: Vec<u8>) {
+        self.segname = v;
+    }
+    pub fn set_sectname(&mut self, v: Vec<u8>) {
+        self.sectname = v;
+    }
+    pub fn set_addr(&mut self, v: u64) {
+        self.addr = v;
+    }
+    pub fn set_size(&mut self, v: u64) {
+        self.size = v;
+    }
+    pub fn set_offset(&mut self, v: u32) {
+        self.offset = v;
+    }
+    pub fn set_align(&mut self, v: u32) {
+        self.align = v;
+    }
+    pub fn set_reloff(&mut self, v: u32) {
+        self.reloff = v;
+    }
+    pub fn set_nreloc(&mut self, v: u32) {
+        self.nreloc = v;
+    }
+    pub fn set_flags(&mut self, v: u32) {
+        self.flags = v;
+    }
+    pub fn set_reserved1(&mut self, v: u32) {
+        self.reserved1 = v;
+    }
+    pub fn set_reserved2(&mut self, v: u32) {
+        self.reserved2 = v;
+    }
+}
+
+#[derive(Default, Debug, Clone)]
+pub(crate) struct Segment {
+    pub segname: Vec<u8>,
+    pub vmaddr: u64,
+    pub vmsize: u64,
+    pub fileoff: u64,
+    pub filesize: u64,
+    pub maxprot: u32,
+    pub initprot: u32,
+    pub nsects: u32,
+    pub flags: u32,
+    pub sections: Vec<Section>,
+}
+
+impl Segment {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn set_segname(&mut self, v: Vec<u8>) {
+        self.segname = v;
+    }
+    pub fn set_vmaddr(&mut self, v: u64) {
+        self.vmaddr = v;
+    }
+    pub fn set_vmsize(&mut self, v: u64) {
+        self.vmsize = v;
+    }
+    pub fn set_fileoff(&mut self, v: u64) {
+        self.fileoff = v;
+    }
+    pub fn set_filesize(&mut self, v: u64) {
+        self.filesize = v;
+    }
+    pub fn set_maxprot(&mut self, v: u32) {
+        self.maxprot = v;
+    }
+    pub fn set_initprot(&mut self, v: u32) {
+        self.initprot = v;
+    }
+    pub fn set_nsects(&mut self, v: u32) {
+        self.nsects = v;
+    }
+    pub fn set_flags(&mut self, v: u32) {
+        self.flags = v;
+    }
+}
+
+#[derive(Default, Debug, Clone)]
+pub(crate) struct Dylib {
+    pub name: Vec<u8>,
+    pub timestamp: u32,
+    pub compatibility_version: String,
+    pub current_version: String,
+}
+
+impl Dylib {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn set_name(&mut self, v: Vec<u8>) {
+        self.name = v;
+    }
+    pub fn set_timestamp(&mut self, v: u32) {
+        self.timestamp = v;
+    }
+    pub fn set_compatibility_version(&mut self, v: String) {
+        self.compatibility_version = v;
+    }
+    pub fn set_current_version(&mut self, v: String) {
+        self.current_version = v;
+    }
+}
+
+#[derive(Default, Debug, Clone)]
+pub(crate) struct Bind {
+    pub symbol: String,
+    pub library: Option<String>,
+    pub segment: Option<u32>,
+    pub address: Option<u64>,
+    pub r#type: u8,
+    pub addend: i64,
+    pub weak: bool,
+}
+
+impl Bind {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn set_symbol(&mut self, v: String) {
+        self.symbol = v;
+    }
+    pub fn set_type(&mut self, v: u8) {
+        self.r#type = v;
+    }
+    pub fn set_addend(&mut self, v: i64) {
+        self.addend = v;
+    }
+    pub fn set_weak(&mut self, v: bool) {
+        self.weak = v;
+    }
+}
+
+#[derive(Default, Debug, Clone)]
+pub(crate) struct Export {
+    pub name: String,
+    pub address: Option<u64>,
+    pub flags: u64,
+    pub reexport_dylib: Option<u64>,
+    pub reexport_name: Option<String>,
+    pub stub_offset: Option<u64>,
+    pub resolver_offset: Option<u64>,
+}
+
+impl Export {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn set_name(&mut self, v: String) {
+        self.name = v;
+    }
+    pub fn set_flags(&mut self, v: u64) {
+        self.flags = v;
+    }
+}
+
+#[derive(Default, Debug, Clone)]
+pub(crate) struct Symtab {
+    pub symoff: u32,
+    pub nsyms: u32,
+    pub stroff: u32,
+    pub strsize: u32,
+    pub entries: Vec<Vec<u8>>,
+}
+
+impl Symtab {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn set_symoff(&mut self, v: u32) {
+        self.symoff = v;
+    }
+    pub fn set_nsyms(&mut self, v: u32) {
+        self.nsyms = v;
+    }
+    pub fn set_stroff(&mut self, v: u32) {
+        self.stroff = v;
+    }
+    pub fn set_strsize(&mut self, v: u32) {
+        self.strsize = v;
+    }
+}
+
+#[derive(Default, Debug, Clone)]
+pub(crate) struct Dysymtab {
+    pub ilocalsym: u32,
+    pub nlocalsym: u32,
+    pub iextdefsym: u32,
+    pub nextdefsym: u32,
+    pub tocoff: u32,
+    pub ntoc: u32,
+    pub modtaboff: u32,
+    pub nmodtab: u32,
+    pub extrefsymoff: u32,
+    pub nextrefsyms: u32,
+    pub indirectsymoff: u32,
+    pub nindirectsyms: u32,
+    pub extreloff: u32,
+    pub nextrel: u32,
+    pub locreloff: u32,
+    pub nlocrel: u32,
+}
+
+impl Dysymtab {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn set_ilocalsym(&mut self, v: u32) {
+        self.ilocalsym = v;
+    }
+    pub fn set_nlocalsym(&mut self, v: u32) {
+        self.nlocalsym = v;
+    }
+    pub fn set_iextdefsym(&mut self, v: u32) {
+        self.iextdefsym = v;
+    }
+    pub fn set_nextdefsym(&mut self, v: u32) {
+        self.nextdefsym = v;
+    }
+    pub fn set_tocoff(&mut self, v: u32) {
+        self.tocoff = v;
+    }
+    pub fn set_ntoc(&mut self, v: u32) {
+        self.ntoc = v;
+    }
+    pub fn set_modtaboff(&mut self, v: u32) {
+        self.modtaboff = v;
+    }
+    pub fn set_nmodtab(&mut self, v: u32) {
+        self.nmodtab = v;
+    }
+    pub fn set_extrefsymoff(&mut self, v: u32) {
+        self.extrefsymoff = v;
+    }
+    pub fn set_nextrefsyms(&mut self, v: u32) {
+        self.nextrefsyms = v;
+    }
+    pub fn set_indirectsymoff(&mut self, v: u32) {
+        self.indirectsymoff = v;
+    }
+    pub fn set_nindirectsyms(&mut self, v: u32) {
+        self.nindirectsyms = v;
+    }
+    pub fn set_extreloff(&mut self, v: u32) {
+        self.extreloff = v;
+    }
+    pub fn set_nextrel(&mut self, v: u32) {
+        self.nextrel = v;
+    }
+    pub fn set_locreloff(&mut self, v: u32) {
+        self.locreloff = v;
+    }
+    pub fn set_nlocrel(&mut self, v: u32) {
+        self.nlocrel = v;
+    }
+}
+
+#[derive(Default, Debug, Clone)]
+pub(crate) struct LinkedItData {
+    pub dataoff: u32,
+    pub datasize: u32,
+}
+
+impl LinkedItData {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn set_dataoff(&mut self, v: u32) {
+        self.dataoff = v;
+    }
+    pub fn set_datasize(&mut self, v: u32) {
+        self.datasize = v;
+    }
+}
+
+#[derive(Default, Debug, Clone)]
+pub(crate) struct Certificate {
+    pub issuer: String,
+    pub subject: String,
+    pub is_self_signed: bool,
+}
+
+impl Certificate {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn set_issuer(&mut self, v: String) {
+        self.issuer = v;
+    }
+    pub fn set_subject(&mut self, v: String) {
+        self.subject = v;
+    }
+    pub fn set_is_self_signed(&mut self, v: bool) {
+        self.is_self_signed = v;
+    }
+}
+
+#[derive(Default, Debug, Clone)]
+pub(crate) struct DyldInfo {
+    pub rebase_off: u32,
+    pub rebase_size: u32,
+    pub bind_off: u32,
+    pub bind_size: u32,
+    pub weak_bind_off: u32,
+    pub weak_bind_size: u32,
+    pub lazy_bind_off: u32,
+    pub lazy_bind_size: u32,
+    pub export_off: u32,
+    pub export_size: u32,
+}
+
+impl DyldInfo {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn set_rebase_off(&mut self, v: u32) {
+        self.rebase_off = v;
+    }
+    pub fn set_rebase_size(&mut self, v: u32) {
+        self.rebase_size = v;
+    }
+    pub fn set_bind_off(&mut self, v: u32) {
+        self.bind_off = v;
+    }
+    pub fn set_bind_size(&mut self, v: u32) {
+        self.bind_size = v;
+    }
+    pub fn set_weak_bind_off(&mut self, v: u32) {
+        self.weak_bind_off = v;
+    }
+    pub fn set_weak_bind_size(&mut self, v: u32) {
+        self.weak_bind_size = v;
+    }
+    pub fn set_lazy_bind_off(&mut self, v: u32) {
+        self.lazy_bind_off = v;
+    }
+    pub fn set_lazy_bind_size(&mut self, v: u32) {
+        self.lazy_bind_size = v;
+    }
+    pub fn set_export_off(&mut self, v: u32) {
+        self.export_off = v;
+    }
+    pub fn set_export_size(&mut self, v: u32) {
+        self.export_size = v;
+    }
+}
+
+#[derive(Default, Debug, Clone)]
+pub(crate) struct BuildTool {
+    pub tool: u32,
+    pub version: String,
+}
+
+impl BuildTool {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn set_tool(&mut self, v: u32) {
+        self.tool = v;
+    }
+    pub fn set_version(&mut self, v: String) {
+        self.version = v;
+    }
+}
+
+#[derive(Default, Debug, Clone)]
+pub(crate) struct BuildVersion {
+    pub platform: u32,
+    pub ntools: u32,
+    pub minos: String,
+    pub sdk: String,
+    pub tools: Vec<BuildTool>,
+}
+
+impl BuildVersion {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn set_platform(&mut self, v: u32) {
+        self.platform = v;
+    }
+    pub fn set_ntools(&mut self, v: u32) {
+        self.ntools = v;
+    }
+    pub fn set_minos(&mut self, v: String) {
+        self.minos = v;
+    }
+    pub fn set_sdk(&mut self, v: String) {
+        self.sdk = v;
+    }
+}
+
+/// Stand-in for what would be a `DeviceType` protobuf enum; kept as a
+/// plain `u32` here rather than a generated `protobuf::Enum` impl, since
+/// the real `protobuf` crate's enum trait surface isn't something this
+/// snapshot can target without the actual codegen pipeline.
+#[derive(Default, Debug, Clone)]
+pub(crate) struct MinVersion {
+    pub device: u32,
+    pub version: String,
+    pub sdk: String,
+}
+
+impl MinVersion {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn set_device(&mut self, v: u32) {
+        self.device = v;
+    }
+    pub fn set_version(&mut self, v: String) {
+        self.version = v;
+    }
+    pub fn set_sdk(&mut self, v: String) {
+        self.sdk = v;
+    }
+}
+
+#[derive(Default, Debug, Clone)]
+pub(crate) struct File {
+    pub magic: Option<u32>,
+    pub ncmds: Option<u32>,
+    pub cputype: Option<u32>,
+    pub cpusubtype: Option<u32>,
+    pub filetype: Option<u32>,
+    pub flags: Option<u32>,
+    pub sizeofcmds: Option<u32>,
+    pub reserved: Option<u32>,
+    pub entry_point: Option<u64>,
+    pub stack_size: Option<u64>,
+    pub source_version: Option<String>,
+    pub dynamic_linker: Option<Vec<u8>>,
+    pub symtab: Option<Symtab>,
+    pub dysymtab: Option<Dysymtab>,
+    pub code_signature_data: Option<LinkedItData>,
+    pub dyld_info: Option<DyldInfo>,
+    pub uuid: Option<String>,
+    pub build_version: Option<BuildVersion>,
+    pub min_version: Option<MinVersion>,
+    pub segments: Vec<Segment>,
+    pub dylibs: Vec<Dylib>,
+    pub rpaths: Vec<Vec<u8>>,
+    pub entitlements: Vec<String>,
+    pub exports: Vec<Export>,
+    pub imports: Vec<String>,
+    pub identifier: Option<String>,
+    pub team_id: Option<String>,
+    pub cdhash: Option<String>,
+    pub hash_type: Option<u32>,
+    pub cs_flags: Option<u32>,
+    pub binds: Vec<Bind>,
+    pub certificates: Vec<Certificate>,
+    pub linker_options: Vec<Vec<u8>>,
+    pub number_of_segments: Option<u32>,
+}
+
+impl File {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn set_magic(&mut self, v: u32) {
+        self.magic = Some(v);
+    }
+    pub fn set_ncmds(&mut self, v: u32) {
+        self.ncmds = Some(v);
+    }
+    pub fn set_cputype(&mut self, v: u32) {
+        self.cputype = Some(v);
+    }
+    pub fn set_cpusubtype(&mut self, v: u32) {
+        self.cpusubtype = Some(v);
+    }
+    pub fn set_filetype(&mut self, v: u32) {
+        self.filetype = Some(v);
+    }
+    pub fn set_flags(&mut self, v: u32) {
+        self.flags = Some(v);
+    }
+    pub fn set_sizeofcmds(&mut self, v: u32) {
+        self.sizeofcmds = Some(v);
+    }
+    pub fn set_number_of_segments(&mut self, v: u32) {
+        self.number_of_segments = Some(v);
+    }
+}
+
+#[derive(Default, Debug, Clone)]
+pub(crate) struct Macho {
+    pub magic: Option<u32>,
+    pub ncmds: Option<u32>,
+    pub cputype: Option<u32>,
+    pub cpusubtype: Option<u32>,
+    pub filetype: Option<u32>,
+    pub flags: Option<u32>,
+    pub sizeofcmds: Option<u32>,
+    pub reserved: Option<u32>,
+    pub entry_point: Option<u64>,
+    pub stack_size: Option<u64>,
+    pub source_version: Option<String>,
+    pub dynamic_linker: Option<Vec<u8>>,
+    pub symtab: Option<Symtab>,
+    pub dysymtab: Option<Dysymtab>,
+    pub code_signature_data: Option<LinkedItData>,
+    pub dyld_info: Option<DyldInfo>,
+    pub uuid: Option<String>,
+    pub build_version: Option<BuildVersion>,
+    pub min_version: Option<MinVersion>,
+    pub segments: Vec<Segment>,
+    pub dylibs: Vec<Dylib>,
+    pub rpaths: Vec<Vec<u8>>,
+    pub entitlements: Vec<String>,
+    pub exports: Vec<Export>,
+    pub imports: Vec<String>,
+    pub identifier: Option<String>,
+    pub team_id: Option<String>,
+    pub cdhash: Option<String>,
+    pub hash_type: Option<u32>,
+    pub cs_flags: Option<u32>,
+    pub binds: Vec<Bind>,
+    pub certificates: Vec<Certificate>,
+    pub linker_options: Vec<Vec<u8>>,
+    pub number_of_segments: Option<u32>,
+    pub fat_magic: Option<u32>,
+    pub nfat_arch: Option<u32>,
+    pub fat_arch: Vec<FatArch>,
+    pub file: Vec<File>,
+    pub images: Vec<File>,
+}
+
+impl Macho {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn set_magic(&mut self, v: u32) {
+        self.magic = Some(v);
+    }
+    pub fn set_ncmds(&mut self, v: u32) {
+        self.ncmds = Some(v);
+    }
+    pub fn set_cputype(&mut self, v: u32) {
+        self.cputype = Some(v);
+    }
+    pub fn set_cpusubtype(&mut self, v: u32) {
+        self.cpusubtype = Some(v);
+    }
+    pub fn set_filetype(&mut self, v: u32) {
+        self.filetype = Some(v);
+    }
+    pub fn set_flags(&mut self, v: u32) {
+        self.flags = Some(v);
+    }
+    pub fn set_sizeofcmds(&mut self, v: u32) {
+        self.sizeofcmds = Some(v);
+    }
+    pub fn set_number_of_segments(&mut self, v: u32) {
+        self.number_of_segments = Some(v);
+    }
+    pub fn set_nfat_arch(&mut self, v: u32) {
+        self.nfat_arch = Some(v);
+    }
+}
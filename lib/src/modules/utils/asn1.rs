@@ -0,0 +1,34 @@
+//! Helpers for pulling X.509 certificates out of a CMS `SignedData`
+//! `certificates` field.
+
+use x509_parser::certificate::X509Certificate;
+
+/// One certificate from a CMS `SignedData` structure's `certificates` set.
+pub(crate) struct SignedData<'a> {
+    pub x509: X509Certificate<'a>,
+}
+
+impl<'a> SignedData<'a> {
+    /// Parses as many consecutive DER-encoded X.509 certificates as
+    /// possible out of `raw_certs`, skipping (and stopping at) anything
+    /// that doesn't parse as one. Certificates in a `SignedData`'s
+    /// `certificates` field are concatenated back-to-back with no extra
+    /// framing, so this just keeps consuming the remainder returned by
+    /// each successful parse.
+    pub fn parse_certificates(raw_certs: &'a [u8]) -> Vec<SignedData<'a>> {
+        let mut result = Vec::new();
+        let mut data = raw_certs;
+
+        while !data.is_empty() {
+            match X509Certificate::from_der(data) {
+                Ok((remainder, x509)) => {
+                    result.push(SignedData { x509 });
+                    data = remainder;
+                }
+                Err(_) => break,
+            }
+        }
+
+        result
+    }
+}
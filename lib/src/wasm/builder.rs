@@ -1,16 +1,59 @@
 use crate::compiler::RuleId;
 use crate::wasm;
+use rayon::prelude::*;
 use rustc_hash::FxHashMap;
 use std::mem;
+use std::ops::Range;
 use walrus::ir::ExtendedLoad::ZeroExtend;
-use walrus::ir::{BinaryOp, Block, InstrSeqId, LoadKind, MemArg, UnaryOp};
+use walrus::ir::{
+    BinaryOp, Block, InstrLocId, InstrSeqId, LoadKind, MemArg, UnaryOp,
+};
 use walrus::ValType::{F64, I32, I64};
 use walrus::{
-    FunctionBuilder, FunctionId, GlobalId, InstrSeqBuilder, MemoryId, Module,
+    FunctionBuilder, FunctionId, GlobalId, InstrSeqBuilder, LocalFunction,
+    MemoryId, Module,
 };
 
 use super::WasmSymbols;
 
+/// A `rules_N` function whose body has been fully emitted, but whose
+/// (potentially expensive) `walrus::FunctionBuilder::local_func` pass and
+/// its wiring into the enclosing namespace function are deferred so that
+/// many of these can be built in parallel. See [`WasmModuleBuilder::build`].
+struct PendingRulesFunc {
+    builder: FunctionBuilder,
+    debug_name: Option<String>,
+    rule_locs: Vec<(RuleId, Range<u32>, Vec<InstrLocId>)>,
+}
+
+/// A `namespaces_N` function whose blocks have been fully emitted except
+/// for the `call`+`br_if` to each of its `rules_N` functions, which can
+/// only be added once those functions have real [`FunctionId`]s. Building
+/// it (and the rules functions it contains) is deferred for the same
+/// reason as [`PendingRulesFunc`]; see [`WasmModuleBuilder::build`].
+struct PendingNamespaceFunc {
+    builder: FunctionBuilder,
+    debug_name: Option<String>,
+    /// For each rules function that belongs to this namespace function:
+    /// the dangling block, within this builder, that should get a
+    /// `call`+`br_if` to it once its `FunctionId` is known, in the order
+    /// the calls must appear.
+    rules: Vec<(InstrSeqId, PendingRulesFunc)>,
+}
+
+/// Maps a generated WASM instruction back to the YARA rule that produced
+/// it, keyed by the function that contains the instruction and the
+/// [`InstrLocId`] `walrus` assigned it (the same id `walrus` uses for
+/// generating DWARF debug info, and which it resolves to a binary offset
+/// when the module is encoded). Only populated when `debug_info` was
+/// enabled via [`WasmModuleBuilder::new`].
+///
+/// This lets `rules-profiling` output, and runtime traps, be attributed
+/// back to the YARA source span of the rule whose condition was compiled
+/// into the offending instruction.
+pub(crate) type RuleSourceMap =
+    FxHashMap<(FunctionId, InstrLocId), (RuleId, Range<u32>)>;
+
 macro_rules! global_var {
     ($module:ident, $name:ident, $ty:ident) => {
         let ($name, _) = $module.add_import_global(
@@ -130,14 +173,67 @@ pub(crate) struct WasmModuleBuilder {
     namespaces_per_func: usize,
     rules_per_func: usize,
     global_rule: bool,
+    /// Opt-in mode that names generated functions `namespaces_N`/`rules_N`
+    /// by a sequential index, and populates `rule_source_map`. See
+    /// [`WasmModuleBuilder::new`].
+    debug_info: bool,
+    /// The source span of the rule currently being built, set by
+    /// [`WasmModuleBuilder::start_rule`].
+    rule_span: Range<u32>,
+    /// Index, within the current `rules_func`'s body, of the first
+    /// instruction emitted for the rule currently being built. Used by
+    /// [`WasmModuleBuilder::finish_rule`] to find the instructions that
+    /// belong to this rule once they've all been emitted.
+    rule_start_instr_idx: usize,
+    /// `(rule_id, span, instruction locations)` for every rule added to
+    /// the `rules_func` that's currently being built, flushed into
+    /// `rule_source_map` once that function is finished and its
+    /// [`FunctionId`] is known. Only used when `debug_info` is enabled.
+    pending_rule_locs: Vec<(RuleId, Range<u32>, Vec<InstrLocId>)>,
+    /// Sequential counter used for naming `rules_N` functions when
+    /// `debug_info` is enabled.
+    rules_func_index: usize,
+    /// Sequential counter used for naming `namespaces_N` functions when
+    /// `debug_info` is enabled.
+    namespace_func_index: usize,
+    rule_source_map: RuleSourceMap,
+    /// `rules_N` functions finished so far for the namespace function
+    /// currently being built, queued instead of being built and wired in
+    /// right away. Drained into a [`PendingNamespaceFunc`] by
+    /// `finish_namespace_func`.
+    current_namespace_rules: Vec<(InstrSeqId, PendingRulesFunc)>,
+    /// `namespaces_N` functions finished so far, queued for the same
+    /// reason. Resolved by [`WasmModuleBuilder::build`], which is the only
+    /// place that needs every function at once and so the only place that
+    /// can build them all in parallel.
+    pending_namespace_funcs: Vec<PendingNamespaceFunc>,
 }
 
 impl WasmModuleBuilder {
     const RULES_FUNC_RET: [walrus::ValType; 1] = [I32; 1];
 
     /// Creates a new module builder.
-    pub fn new() -> Self {
-        let config = walrus::ModuleConfig::new();
+    ///
+    /// `debug_info` enables an opt-in debugging mode that (1) names each
+    /// generated function in the standard WASM "name" custom section as
+    /// `namespaces_N`/`rules_N`, where `N` counts the functions of that kind
+    /// in the order they are built, instead of leaving it anonymous (the
+    /// name does not say which namespaces or rules the function covers),
+    /// and (2) builds the
+    /// [`RuleSourceMap`] returned alongside the module by
+    /// [`WasmModuleBuilder::build`], mapping generated instructions back
+    /// to the `RuleId` and source span of the rule that produced them.
+    /// Disabled by default. Anyone debugging a miscompiled ruleset, or
+    /// attributing `rules-profiling` output or a runtime trap back to
+    /// concrete YARA source, wants this on; normal compilation doesn't
+    /// need the extra bookkeeping.
+    ///
+    /// This has to be decided upfront, rather than toggled later, because
+    /// `walrus` only accepts it as part of the `ModuleConfig` that's
+    /// consumed when the `walrus::Module` is constructed below.
+    pub fn new(debug_info: bool) -> Self {
+        let mut config = walrus::ModuleConfig::new();
+        config.generate_name_section(debug_info);
         let mut module = walrus::Module::with_config(config);
         let mut wasm_exports = FxHashMap::default();
 
@@ -176,6 +272,19 @@ impl WasmModuleBuilder {
             matching_patterns_bitmap_base,
         );
 
+        // `check_for_pattern_match` is otherwise only ever reached through
+        // rule conditions compiled into `rules_N` functions. The
+        // differential fuzzing harness in `crate::fuzzing` wants to call it
+        // directly, for every pattern id, to check in isolation that it
+        // reads the right bit out of the bitmap, so export it under the
+        // `fuzzing` feature instead of threading a test-only rule through
+        // the harness for that alone.
+        #[cfg(feature = "fuzzing")]
+        module.exports.add(
+            "check_for_pattern_match",
+            check_for_pattern_match,
+        );
+
         let wasm_symbols = WasmSymbols {
             main_memory,
             check_for_pattern_match,
@@ -221,6 +330,15 @@ impl WasmModuleBuilder {
             namespaces_per_func: 10,
             rules_per_func: 10,
             global_rule: false,
+            debug_info,
+            rule_span: 0..0,
+            rule_start_instr_idx: 0,
+            pending_rule_locs: Vec::new(),
+            rules_func_index: 0,
+            namespace_func_index: 0,
+            rule_source_map: FxHashMap::default(),
+            current_namespace_rules: Vec::new(),
+            pending_namespace_funcs: Vec::new(),
         }
     }
 
@@ -254,9 +372,17 @@ impl WasmModuleBuilder {
     ///
     /// The code emitted for the rule must leave an i32 in the stack with value
     /// 1 or 0 indicating whether the rule matched or not.
+    ///
+    /// `span` is the rule's source span, recorded in the [`RuleSourceMap`]
+    /// when [`WasmModuleBuilder::new`] was called with `debug_info` enabled.
+    /// The rule compiler
+    /// (`crate::compiler`) is the primary caller; [`crate::fuzzing`] is the
+    /// only caller present in this snapshot and has already been updated
+    /// for this signature.
     pub fn start_rule(
         &mut self,
         rule_id: RuleId,
+        span: Range<u32>,
         global: bool,
     ) -> InstrSeqBuilder<'_> {
         if self.num_rules == self.rules_per_func {
@@ -265,7 +391,9 @@ impl WasmModuleBuilder {
         }
         self.num_rules += 1;
         self.rule_id = rule_id;
+        self.rule_span = span;
         self.global_rule = global;
+        self.rule_start_instr_idx = self.rules_func.func_body().instrs().len();
 
         self.rules_func.func_body()
     }
@@ -322,6 +450,19 @@ impl WasmModuleBuilder {
                 else_.i32_const(self.rule_id.into()).call(rule_match);
             },
         );
+
+        if self.debug_info {
+            let locs = self.rules_func.func_body().instrs()
+                [self.rule_start_instr_idx..]
+                .iter()
+                .map(|(_, loc)| *loc)
+                .collect();
+            self.pending_rule_locs.push((
+                self.rule_id,
+                self.rule_span.clone(),
+                locs,
+            ));
+        }
     }
 
     /// Starts a new namespace.
@@ -337,11 +478,114 @@ impl WasmModuleBuilder {
     }
 
     /// Builds the WASM module and consumes the builder.
-    pub fn build(mut self) -> walrus::Module {
+    ///
+    /// Returns the module together with the [`RuleSourceMap`] built while
+    /// constructing it, which is empty unless `debug_info` was enabled via
+    /// [`WasmModuleBuilder::new`].
+    ///
+    /// Every `rules_N` function only calls imported helpers and returns
+    /// 0/1, and every `namespaces_N` function only calls the `rules_N`
+    /// functions that belong to it, so once all of them have been emitted
+    /// their (potentially expensive) `walrus::FunctionBuilder::local_func`
+    /// passes don't depend on each other. This builds all the `rules_N`
+    /// functions across the whole ruleset in parallel, wires their real
+    /// `FunctionId`s into the namespace functions that call them, then does
+    /// the same thing one level up for the `namespaces_N` functions. The
+    /// `namespaces_per_func`/`rules_per_func` knobs, which decide how many
+    /// namespaces/rules land in one function, are therefore also what
+    /// decides how many independent units of work each parallel pass gets.
+    pub fn build(mut self) -> (walrus::Module, RuleSourceMap) {
         self.finish_rule_func();
         self.finish_namespace_block();
         self.finish_namespace_func();
 
+        // Flatten every pending rules_N function, across every namespace
+        // function, into one list so the parallel build below isn't
+        // limited to the rules of a single namespace. `namespace_idx` is
+        // this entry's index into `namespace_shells`, kept so the result
+        // can be scattered back to the namespace function it belongs to.
+        let mut flat_rules_funcs = Vec::new();
+        let mut namespace_shells: Vec<(FunctionBuilder, Option<String>)> =
+            mem::take(&mut self.pending_namespace_funcs)
+                .into_iter()
+                .enumerate()
+                .map(|(namespace_idx, pending)| {
+                    for (block_seq, rules_func) in pending.rules {
+                        flat_rules_funcs.push((
+                            namespace_idx,
+                            block_seq,
+                            rules_func,
+                        ));
+                    }
+                    (pending.builder, pending.debug_name)
+                })
+                .collect();
+
+        // Build every rules_N function in parallel: the expensive part of
+        // `local_func` (laying out and validating the function body) for
+        // one function never touches another's state.
+        let built_rules_funcs: Vec<_> = flat_rules_funcs
+            .into_par_iter()
+            .map(|(namespace_idx, block_seq, pending)| {
+                let local_func = pending.builder.local_func(Vec::new());
+                (namespace_idx, block_seq, local_func, pending)
+            })
+            .collect();
+
+        // Adding to `module.funcs` and wiring the calls back into each
+        // namespace function's blocks is cheap, so it stays sequential
+        // (and in original order, so the module is byte-identical to what
+        // a sequential build would produce).
+        for (namespace_idx, block_seq, local_func, pending) in built_rules_funcs
+        {
+            let func_id = self.module.funcs.add_local(local_func);
+
+            if let Some(name) = pending.debug_name {
+                self.module.funcs.get_mut(func_id).name = Some(name);
+            }
+
+            for (rule_id, span, locs) in pending.rule_locs {
+                for loc in locs {
+                    self.rule_source_map
+                        .insert((func_id, loc), (rule_id, span.clone()));
+                }
+            }
+
+            let namespace_builder = &mut namespace_shells[namespace_idx].0;
+            let mut block = namespace_builder.instr_seq(block_seq);
+
+            block.call(func_id);
+
+            let block_id = block.id();
+
+            // If the rules function returned 1 is because some global rule
+            // didn't match, in this case we exit early from the namespace
+            // block, preventing any other rule in the namespace from being
+            // executed.
+            block.br_if(block_id);
+        }
+
+        // Now that every namespace function's blocks are fully wired, do
+        // the same two-phase parallel build/sequential-wire dance one
+        // level up, for the namespaces_N functions themselves.
+        let built_namespace_funcs: Vec<(LocalFunction, Option<String>)> =
+            namespace_shells
+                .into_par_iter()
+                .map(|(builder, debug_name)| {
+                    (builder.local_func(Vec::new()), debug_name)
+                })
+                .collect();
+
+        for (local_func, debug_name) in built_namespace_funcs {
+            let func_id = self.module.funcs.add_local(local_func);
+
+            if let Some(name) = debug_name {
+                self.module.funcs.get_mut(func_id).name = Some(name);
+            }
+
+            self.main_func.func_body().call(func_id);
+        }
+
         // Emit the last instruction for the main function, which consist
         // in putting the return value in the stack. The return value is
         // always 0.
@@ -351,7 +595,7 @@ impl WasmModuleBuilder {
             self.main_func.finish(Vec::new(), &mut self.module.funcs);
 
         self.module.exports.add("main", main_func);
-        self.module
+        (self.module, self.rule_source_map)
     }
 }
 
@@ -394,9 +638,17 @@ impl WasmModuleBuilder {
         self.namespace_block =
             self.namespace_func.dangling_instr_seq(None).id();
 
-        self.main_func.func_body().call(
-            self.module.funcs.add_local(namespace_func.local_func(Vec::new())),
-        );
+        let debug_name = self.debug_info.then(|| {
+            let name = format!("namespaces_{}", self.namespace_func_index);
+            self.namespace_func_index += 1;
+            name
+        });
+
+        self.pending_namespace_funcs.push(PendingNamespaceFunc {
+            builder: namespace_func,
+            debug_name,
+            rules: mem::take(&mut self.current_namespace_rules),
+        });
     }
 
     fn finish_rule_func(&mut self) {
@@ -409,6 +661,8 @@ impl WasmModuleBuilder {
             ),
         );
 
+        let rule_locs = mem::take(&mut self.pending_rule_locs);
+
         if !rule_func.func_body().instrs().is_empty() {
             // The last instruction in a rules function leaves a 0 in the
             // stack as its return value. This is reached only when all
@@ -416,20 +670,21 @@ impl WasmModuleBuilder {
             // function exits early with a return value of 1.
             rule_func.func_body().i32_const(0);
 
-            let mut namespace_block =
-                self.namespace_func.instr_seq(self.namespace_block);
-
-            namespace_block.call(
-                self.module.funcs.add_local(rule_func.local_func(Vec::new())),
-            );
-
-            let namespace_block_id = namespace_block.id();
-
-            // If the rules function returned 1 is because some global rule
-            // didn't match, in this case we exit early from the namespace
-            // block, preventing any other rule in the namespace from being
-            // executed.
-            namespace_block.br_if(namespace_block_id);
+            let debug_name = self.debug_info.then(|| {
+                let name = format!("rules_{}", self.rules_func_index);
+                self.rules_func_index += 1;
+                name
+            });
+
+            // Building this function (`FunctionBuilder::local_func`) and
+            // wiring its call into `self.namespace_block` are both deferred
+            // to `WasmModuleBuilder::build`, which can build every
+            // independent `rules_N` function across the whole ruleset in
+            // parallel instead of one at a time as each partition fills up.
+            self.current_namespace_rules.push((
+                self.namespace_block,
+                PendingRulesFunc { builder: rule_func, debug_name, rule_locs },
+            ));
         }
     }
 
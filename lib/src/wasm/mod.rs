@@ -0,0 +1,4 @@
+//! WASM code generation: turns a compiled rule set into an executable
+//! WASM module.
+
+pub(crate) mod builder;
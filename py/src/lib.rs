@@ -21,19 +21,26 @@ use std::ops::Deref;
 use std::path::PathBuf;
 use std::pin::Pin;
 use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc;
+use std::sync::Arc;
 use std::time::Duration;
 use strum_macros::{Display, EnumString};
 
 use base64::prelude::BASE64_STANDARD;
 use base64::Engine;
+use protobuf::reflect::{ReflectFieldRef, ReflectValueRef};
 use protobuf::MessageDyn;
-use pyo3::exceptions::{PyException, PyIOError, PyTypeError, PyValueError};
+use pyo3::exceptions::{
+    PyException, PyIOError, PyIndexError, PyTypeError, PyValueError,
+};
 use pyo3::prelude::*;
 use pyo3::types::{
-    PyBool, PyBytes, PyDict, PyFloat, PyInt, PyString, PyStringMethods,
-    PyTuple, PyTzInfo,
+    PyBool, PyBytes, PyDict, PyFloat, PyInt, PyList, PyString,
+    PyStringMethods, PyTuple, PyTzInfo,
 };
 use pyo3::{create_exception, IntoPyObjectExt};
+use pyo3_async_runtimes::tokio::future_into_py;
 use pyo3_file::PyFileLikeObject;
 
 use ::yara_x as yrx;
@@ -168,6 +175,51 @@ impl Module {
     }
 }
 
+/// Invokes the `macho` module and returns its output.
+#[pyfunction]
+fn macho<'py>(py: Python<'py>, data: &[u8]) -> PyResult<Bound<'py, PyAny>> {
+    match invoke_dyn::<Macho>(data) {
+        Some(output) => proto_to_json(py, output.as_ref()),
+        None => Ok(py.None().into_bound(py)),
+    }
+}
+
+/// Invokes the `lnk` module and returns its output.
+#[pyfunction]
+fn lnk<'py>(py: Python<'py>, data: &[u8]) -> PyResult<Bound<'py, PyAny>> {
+    match invoke_dyn::<Lnk>(data) {
+        Some(output) => proto_to_json(py, output.as_ref()),
+        None => Ok(py.None().into_bound(py)),
+    }
+}
+
+/// Invokes the `elf` module and returns its output.
+#[pyfunction]
+fn elf<'py>(py: Python<'py>, data: &[u8]) -> PyResult<Bound<'py, PyAny>> {
+    match invoke_dyn::<ELF>(data) {
+        Some(output) => proto_to_json(py, output.as_ref()),
+        None => Ok(py.None().into_bound(py)),
+    }
+}
+
+/// Invokes the `pe` module and returns its output.
+#[pyfunction]
+fn pe<'py>(py: Python<'py>, data: &[u8]) -> PyResult<Bound<'py, PyAny>> {
+    match invoke_dyn::<PE>(data) {
+        Some(output) => proto_to_json(py, output.as_ref()),
+        None => Ok(py.None().into_bound(py)),
+    }
+}
+
+/// Invokes the `dotnet` module and returns its output.
+#[pyfunction]
+fn dotnet<'py>(py: Python<'py>, data: &[u8]) -> PyResult<Bound<'py, PyAny>> {
+    match invoke_dyn::<Dotnet>(data) {
+        Some(output) => proto_to_json(py, output.as_ref()),
+        None => Ok(py.None().into_bound(py)),
+    }
+}
+
 /// Compiles a YARA source code producing a set of compiled [`Rules`].
 ///
 /// This function allows compiling simple rules that don't depend on external
@@ -187,6 +239,23 @@ struct Compiler {
     relaxed_re_syntax: bool,
     error_on_slow_pattern: bool,
     includes_enabled: bool,
+    on_error: Option<PyObject>,
+    on_warning: Option<PyObject>,
+}
+
+/// Converts a single compiler diagnostic (an error or a warning) into a
+/// Python dict with `code`, `message`, `origin` and `span` keys, the same
+/// shape produced by [`Compiler::errors`] and [`Compiler::warnings`] for
+/// each of their items.
+fn diagnostic_to_py<'py, T: serde::Serialize>(
+    py: Python<'py>,
+    diagnostic: &T,
+) -> PyResult<Bound<'py, PyAny>> {
+    let json = PyModule::import(py, "json")?;
+    let json_loads = json.getattr("loads")?;
+    let diagnostic_json = serde_json::to_string(diagnostic)
+        .map_err(|err| PyValueError::new_err(err.to_string()))?;
+    json_loads.call((diagnostic_json,), None)
 }
 
 impl Compiler {
@@ -231,6 +300,8 @@ impl Compiler {
             relaxed_re_syntax,
             error_on_slow_pattern,
             includes_enabled,
+            on_error: None,
+            on_warning: None,
         };
         compiler.inner.enable_includes(includes_enabled);
         compiler
@@ -249,6 +320,62 @@ impl Compiler {
         Ok(())
     }
 
+    /// Specify a regular expression that the compiler will enforce upon each
+    /// rule tag. Any rule containing a tag that does not match this regex
+    /// will return an InvalidTag warning.
+    ///
+    /// If the regexp does not compile a ValueError is returned.
+    #[pyo3(signature = (regexp_str))]
+    fn tag_regexp(&mut self, regexp_str: &str) -> PyResult<()> {
+        let linter = yrx::linters::tags(regexp_str)
+            .map_err(|err| PyValueError::new_err(err.to_string()))?;
+        self.inner.add_linter(linter);
+        Ok(())
+    }
+
+    /// Requires that every rule defines metadata identifiers matching this
+    /// regular expression. Rules that don't will return a MissingMetadata
+    /// warning.
+    ///
+    /// If the regexp does not compile a ValueError is returned.
+    #[pyo3(signature = (regexp_str))]
+    fn required_metadata_regexp(&mut self, regexp_str: &str) -> PyResult<()> {
+        let linter = yrx::linters::metadata(regexp_str)
+            .map_err(|err| PyValueError::new_err(err.to_string()))?;
+        self.inner.add_linter(linter);
+        Ok(())
+    }
+
+    /// Sets a callback that is invoked for every error produced while
+    /// compiling the source code passed to [`Compiler::add_source`].
+    ///
+    /// The callback is called once per error, right after the call to
+    /// [`Compiler::add_source`] that produced it, with a single argument: a
+    /// dict with the `code`, `message`, `origin` and `span` of the error, in
+    /// the same format used by [`Compiler::errors`].
+    fn on_error(&mut self, callback: PyObject) -> PyResult<()> {
+        if !Python::with_gil(|py| callback.bind(py).is_callable()) {
+            return Err(PyValueError::new_err("callback is not callable"));
+        }
+        self.on_error = Some(callback);
+        Ok(())
+    }
+
+    /// Sets a callback that is invoked for every warning produced while
+    /// compiling the source code passed to [`Compiler::add_source`].
+    ///
+    /// The callback is called once per warning, right after the call to
+    /// [`Compiler::add_source`] that produced it, with a single argument: a
+    /// dict with the `code`, `message`, `origin` and `span` of the warning,
+    /// in the same format used by [`Compiler::warnings`].
+    fn on_warning(&mut self, callback: PyObject) -> PyResult<()> {
+        if !Python::with_gil(|py| callback.bind(py).is_callable()) {
+            return Err(PyValueError::new_err("callback is not callable"));
+        }
+        self.on_warning = Some(callback);
+        Ok(())
+    }
+
     /// Adds a YARA source code to be compiled.
     ///
     /// This function may be invoked multiple times to add several sets of YARA
@@ -278,9 +405,30 @@ impl Compiler {
             src = src.with_origin(origin)
         }
 
-        self.inner
-            .add_source(src)
-            .map_err(|err| CompileError::new_err(err.to_string()))?;
+        let errors_before = self.inner.errors().len();
+        let warnings_before = self.inner.warnings().len();
+
+        let result = self.inner.add_source(src);
+
+        if let Some(callback) = &self.on_error {
+            Python::with_gil(|py| -> PyResult<()> {
+                for err in &self.inner.errors()[errors_before..] {
+                    callback.call1(py, (diagnostic_to_py(py, err)?,))?;
+                }
+                Ok(())
+            })?;
+        }
+
+        if let Some(callback) = &self.on_warning {
+            Python::with_gil(|py| -> PyResult<()> {
+                for warning in &self.inner.warnings()[warnings_before..] {
+                    callback.call1(py, (diagnostic_to_py(py, warning)?,))?;
+                }
+                Ok(())
+            })?;
+        }
+
+        result.map_err(|err| CompileError::new_err(err.to_string()))?;
 
         Ok(())
     }
@@ -351,6 +499,27 @@ impl Compiler {
         Ok(())
     }
 
+    /// Defines several global variables at once.
+    ///
+    /// This is equivalent to calling [`Compiler::define_global`] for every
+    /// item in `globals`, but doing it all at once is more convenient when
+    /// you have many variables to define.
+    ///
+    /// # Raises
+    ///
+    /// [TypeError](https://docs.python.org/3/library/exceptions.html#TypeError)
+    /// if the type of some value in `globals` is not one of the supported
+    /// ones.
+    fn define_globals(
+        &mut self,
+        globals: &Bound<PyDict>,
+    ) -> PyResult<()> {
+        for (ident, value) in globals.iter() {
+            self.define_global(ident.extract::<String>()?.as_str(), value)?;
+        }
+        Ok(())
+    }
+
     /// Creates a new namespace.
     ///
     /// Further calls to [`Compiler::add_source`] will put the rules under the
@@ -434,6 +603,53 @@ impl Compiler {
     }
 }
 
+// A unit of work for `ScannerWorker`'s thread: a closure that only captures
+// `Send` data, so it can cross the channel even though `yrx::Scanner` itself
+// generally can't.
+type ScannerJob = Box<dyn FnOnce(&mut yrx::Scanner<'static>) + Send>;
+
+/// Owns a `yrx::Scanner` on a dedicated worker thread and runs closures
+/// against it one at a time, over a channel.
+///
+/// `yrx::Scanner` borrows from the `yrx::Rules` it was created with and
+/// isn't `Send`, so it can't be moved into the background thread that
+/// `Scanner::scan_async`/`scan_file_async` use to avoid blocking the Python
+/// event loop. Rather than asserting it's safe to move anyway, the scanner
+/// is instead created on, and never leaves, the thread spawned here; only
+/// the `ScannerJob` closures sent to it (and their results, sent back) need
+/// to be `Send`.
+struct ScannerWorker {
+    job_tx: mpsc::Sender<ScannerJob>,
+}
+
+impl ScannerWorker {
+    fn new(rules: &'static yrx::Rules) -> Self {
+        let (job_tx, job_rx) = mpsc::channel::<ScannerJob>();
+        std::thread::spawn(move || {
+            let mut scanner = yrx::Scanner::new(rules);
+            while let Ok(job) = job_rx.recv() {
+                job(&mut scanner);
+            }
+        });
+        Self { job_tx }
+    }
+
+    /// Runs `f` against the scanner on its worker thread, blocking the
+    /// calling thread until it completes.
+    fn call<R: Send + 'static>(
+        &self,
+        f: impl FnOnce(&mut yrx::Scanner<'static>) -> R + Send + 'static,
+    ) -> R {
+        let (result_tx, result_rx) = mpsc::channel();
+        self.job_tx
+            .send(Box::new(move |scanner| {
+                let _ = result_tx.send(f(scanner));
+            }))
+            .expect("scanner worker thread is gone");
+        result_rx.recv().expect("scanner worker thread panicked")
+    }
+}
+
 /// Scans data with already compiled YARA rules.
 ///
 /// The scanner receives a set of compiled Rules and scans data with those
@@ -452,7 +668,7 @@ struct Scanner {
     // `yrx::Rules` are pinned, so that they are not moved from their
     // original location and the reference remains valid.
     _rules: Py<Rules>,
-    inner: yrx::Scanner<'static>,
+    inner: Arc<ScannerWorker>,
 }
 
 #[pymethods]
@@ -466,7 +682,10 @@ impl Scanner {
                 let rules_ptr: *const yrx::Rules = &rules.deref().inner.rules;
                 unsafe { &*rules_ptr }
             };
-            Self { _rules: rules, inner: yrx::Scanner::new(rules_ref) }
+            Self {
+                _rules: rules,
+                inner: Arc::new(ScannerWorker::new(rules_ref)),
+            }
         })
     }
 
@@ -490,33 +709,88 @@ impl Scanner {
         ident: &str,
         value: Bound<PyAny>,
     ) -> PyResult<()> {
-        let result = if value.is_exact_instance_of::<PyBool>() {
-            self.inner.set_global(ident, value.extract::<bool>()?)
-        } else if value.is_exact_instance_of::<PyString>() {
-            self.inner.set_global(ident, value.extract::<String>()?)
-        } else if value.is_exact_instance_of::<PyBytes>() {
-            self.inner.set_global(ident, value.extract::<&[u8]>()?)
-        } else if value.is_exact_instance_of::<PyInt>() {
-            self.inner.set_global(ident, value.extract::<i64>()?)
-        } else if value.is_exact_instance_of::<PyFloat>() {
-            self.inner.set_global(ident, value.extract::<f64>()?)
-        } else {
-            return Err(PyTypeError::new_err(format!(
-                "unsupported variable type `{}`",
-                value.get_type()
-            )));
-        };
+        let ident = ident.to_string();
+        // Errors are converted to a `String` inside the closure, before it
+        // crosses over to the worker thread's channel, since the error type
+        // `yrx::Scanner::set_global` returns isn't guaranteed to be `Send`.
+        let result: Result<(), String> =
+            if value.is_exact_instance_of::<PyBool>() {
+                let value = value.extract::<bool>()?;
+                self.inner.call(move |scanner| {
+                    scanner
+                        .set_global(&ident, value)
+                        .map(|_| ())
+                        .map_err(|err| err.to_string())
+                })
+            } else if value.is_exact_instance_of::<PyString>() {
+                let value = value.extract::<String>()?;
+                self.inner.call(move |scanner| {
+                    scanner
+                        .set_global(&ident, value)
+                        .map(|_| ())
+                        .map_err(|err| err.to_string())
+                })
+            } else if value.is_exact_instance_of::<PyBytes>() {
+                let value = value.extract::<Vec<u8>>()?;
+                self.inner.call(move |scanner| {
+                    scanner
+                        .set_global(&ident, value)
+                        .map(|_| ())
+                        .map_err(|err| err.to_string())
+                })
+            } else if value.is_exact_instance_of::<PyInt>() {
+                let value = value.extract::<i64>()?;
+                self.inner.call(move |scanner| {
+                    scanner
+                        .set_global(&ident, value)
+                        .map(|_| ())
+                        .map_err(|err| err.to_string())
+                })
+            } else if value.is_exact_instance_of::<PyFloat>() {
+                let value = value.extract::<f64>()?;
+                self.inner.call(move |scanner| {
+                    scanner
+                        .set_global(&ident, value)
+                        .map(|_| ())
+                        .map_err(|err| err.to_string())
+                })
+            } else {
+                return Err(PyTypeError::new_err(format!(
+                    "unsupported variable type `{}`",
+                    value.get_type()
+                )));
+            };
 
-        result.map_err(|err| PyValueError::new_err(err.to_string()))?;
+        result.map_err(PyValueError::new_err)?;
 
         Ok(())
     }
 
+    /// Sets the value of several global variables at once.
+    ///
+    /// This is equivalent to calling [`Scanner::set_global`] for every item
+    /// in `globals`, but doing it all at once is more convenient when you
+    /// have many variables to set.
+    ///
+    /// # Raises
+    ///
+    /// [TypeError](https://docs.python.org/3/library/exceptions.html#TypeError)
+    /// if the type of some value in `globals` is not one of the supported
+    /// ones.
+    fn set_globals(&mut self, globals: &Bound<PyDict>) -> PyResult<()> {
+        for (ident, value) in globals.iter() {
+            self.set_global(ident.extract::<String>()?.as_str(), value)?;
+        }
+        Ok(())
+    }
+
     /// Sets a timeout for each scan.
     ///
     /// After setting a timeout scans will abort after the specified `seconds`.
     fn set_timeout(&mut self, seconds: u64) {
-        self.inner.set_timeout(Duration::from_secs(seconds));
+        self.inner.call(move |scanner| {
+            scanner.set_timeout(Duration::from_secs(seconds));
+        });
     }
 
     /// Sets a callback that is invoked every time a YARA rule calls the
@@ -530,31 +804,90 @@ impl Scanner {
         if !Python::with_gil(|py| callback.bind(py).is_callable()) {
             return Err(PyValueError::new_err("callback is not callable"));
         }
-        self.inner.console_log(move |msg| {
-            let _ = Python::with_gil(|py| -> PyResult<PyObject> {
-                callback.call1(py, (msg,))
+        self.inner.call(move |scanner| {
+            scanner.console_log(move |msg| {
+                let _ = Python::with_gil(|py| -> PyResult<PyObject> {
+                    callback.call1(py, (msg,))
+                });
             });
         });
         Ok(())
     }
 
     /// Scans in-memory data.
-    fn scan(&mut self, data: &[u8]) -> PyResult<Py<ScanResults>> {
-        Python::with_gil(|py| {
-            scan_results_to_py(
-                py,
-                self.inner.scan(data).map_err(map_scan_err)?,
-            )
+    fn scan(&mut self, py: Python, data: &[u8]) -> PyResult<Py<ScanResults>> {
+        let data = data.to_vec();
+        let inner = self.inner.clone();
+        py.allow_threads(|| {
+            inner.call(move |scanner| {
+                let results = scanner.scan(&data).map_err(map_scan_err)?;
+                Python::with_gil(|py| scan_results_to_py(py, results))
+            })
         })
     }
 
     /// Scans a file.
-    fn scan_file(&mut self, path: PathBuf) -> PyResult<Py<ScanResults>> {
-        Python::with_gil(|py| {
-            scan_results_to_py(
-                py,
-                self.inner.scan_file(path).map_err(map_scan_err)?,
-            )
+    fn scan_file(
+        &mut self,
+        py: Python,
+        path: PathBuf,
+    ) -> PyResult<Py<ScanResults>> {
+        let inner = self.inner.clone();
+        py.allow_threads(|| {
+            inner.call(move |scanner| {
+                let results = scanner.scan_file(path).map_err(map_scan_err)?;
+                Python::with_gil(|py| scan_results_to_py(py, results))
+            })
+        })
+    }
+
+    /// Scans in-memory data without blocking the calling thread.
+    ///
+    /// Returns an awaitable that resolves to the same result as
+    /// [`Scanner::scan`]. The scan runs on a background thread, so other
+    /// Python coroutines can make progress while it's in flight. Calling
+    /// this method again before a previous `scan_async`/`scan_file_async`
+    /// call on the same scanner has finished will block the background
+    /// thread until the scanner is free, since a single `Scanner` can only
+    /// run one scan at a time.
+    fn scan_async<'py>(
+        &self,
+        py: Python<'py>,
+        data: Vec<u8>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let inner = self.inner.clone();
+        future_into_py(py, async move {
+            tokio::task::spawn_blocking(move || {
+                inner.call(move |scanner| {
+                    let results =
+                        scanner.scan(&data).map_err(map_scan_err)?;
+                    Python::with_gil(|py| scan_results_to_py(py, results))
+                })
+            })
+            .await
+            .expect("scan thread panicked")
+        })
+    }
+
+    /// Scans a file without blocking the calling thread.
+    ///
+    /// See [`Scanner::scan_async`] for details.
+    fn scan_file_async<'py>(
+        &self,
+        py: Python<'py>,
+        path: PathBuf,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let inner = self.inner.clone();
+        future_into_py(py, async move {
+            tokio::task::spawn_blocking(move || {
+                inner.call(move |scanner| {
+                    let results =
+                        scanner.scan_file(path).map_err(map_scan_err)?;
+                    Python::with_gil(|py| scan_results_to_py(py, results))
+                })
+            })
+            .await
+            .expect("scan thread panicked")
         })
     }
 }
@@ -585,6 +918,54 @@ impl ScanResults {
     ) -> &'py Bound<'py, PyDict> {
         self.module_outputs.bind(py)
     }
+
+    /// Returns the module output from the scan in the given format.
+    ///
+    /// `fmt` must be one of `"dict"` (a native Python dictionary, the same
+    /// as the [`ScanResults::module_outputs`] property), `"json"` or
+    /// `"yaml"`.
+    ///
+    /// # Raises
+    ///
+    /// [ValueError](https://docs.python.org/3/library/exceptions.html#ValueError)
+    /// if `fmt` is not one of the supported formats.
+    fn module_outputs_as<'py>(
+        &'py self,
+        py: Python<'py>,
+        fmt: &str,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let outputs = self.module_outputs.bind(py);
+        match fmt {
+            "dict" => Ok(outputs.clone().into_any()),
+            "json" => PyModule::import(py, "json")?
+                .getattr("dumps")?
+                .call1((outputs,)),
+            "yaml" => PyModule::import(py, "yaml")?
+                .getattr("dump")?
+                .call1((outputs,)),
+            _ => Err(PyValueError::new_err(format!(
+                "unsupported format `{fmt}`, expected one of: dict, json, yaml"
+            ))),
+        }
+    }
+
+    /// Returns the number of rules that matched during the scan.
+    fn __len__(&self, py: Python) -> usize {
+        self.matching_rules.bind(py).len()
+    }
+
+    /// Returns the matching rule at the given index.
+    fn __getitem__<'py>(
+        &self,
+        py: Python<'py>,
+        index: isize,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        tuple_get_item(self.matching_rules.bind(py), index)
+    }
+
+    fn __iter__<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        self.matching_rules.bind(py).as_any().call_method0("__iter__")
+    }
 }
 
 /// Represents a rule that matched while scanning some data.
@@ -629,13 +1010,34 @@ impl Rule {
     fn patterns(&self) -> Py<PyTuple> {
         Python::with_gil(|py| self.patterns.clone_ref(py))
     }
+
+    /// Returns the number of patterns defined by the rule.
+    fn __len__(&self, py: Python) -> usize {
+        self.patterns.bind(py).len()
+    }
+
+    /// Returns the pattern at the given index.
+    fn __getitem__<'py>(
+        &self,
+        py: Python<'py>,
+        index: isize,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        tuple_get_item(self.patterns.bind(py), index)
+    }
+
+    fn __iter__<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        self.patterns.bind(py).as_any().call_method0("__iter__")
+    }
 }
 
 /// Represents a pattern in a YARA rule.
 #[pyclass]
 struct Pattern {
     identifier: String,
-    matches: Py<PyTuple>,
+    // Matches are kept in this raw form, instead of as a tuple of `Match`
+    // objects, so that `Match` objects are only created on demand, when
+    // the caller actually asks for a specific match or iterates over them.
+    matches: Vec<(usize, usize, Option<u8>)>,
 }
 
 #[pymethods]
@@ -648,8 +1050,65 @@ impl Pattern {
 
     /// Matches found for this pattern.
     #[getter]
-    fn matches(&self) -> Py<PyTuple> {
-        Python::with_gil(|py| self.matches.clone_ref(py))
+    fn matches(&self, py: Python) -> PyResult<Py<PyTuple>> {
+        let matches = self
+            .matches
+            .iter()
+            .map(|m| Py::new(py, match_from_tuple(*m)))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(PyTuple::new(py, matches)?.unbind())
+    }
+
+    /// Returns the number of matches found for this pattern.
+    fn __len__(&self) -> usize {
+        self.matches.len()
+    }
+
+    /// Returns the match at the given index.
+    fn __getitem__(&self, py: Python, index: isize) -> PyResult<Py<Match>> {
+        let len = self.matches.len() as isize;
+        let i = if index < 0 { index + len } else { index };
+        if i < 0 || i >= len {
+            return Err(PyIndexError::new_err("index out of range"));
+        }
+        Py::new(py, match_from_tuple(self.matches[i as usize]))
+    }
+
+    fn __iter__(slf: Py<Self>) -> MatchIterator {
+        MatchIterator { pattern: slf, index: 0 }
+    }
+}
+
+fn match_from_tuple(m: (usize, usize, Option<u8>)) -> Match {
+    Match { offset: m.0, length: m.1, xor_key: m.2 }
+}
+
+/// Lazily yields [`Match`] objects for a [`Pattern`], one at a time, instead
+/// of materializing all of them up front.
+#[pyclass]
+struct MatchIterator {
+    pattern: Py<Pattern>,
+    index: usize,
+}
+
+#[pymethods]
+impl MatchIterator {
+    fn __iter__(slf: PyRef<Self>) -> PyRef<Self> {
+        slf
+    }
+
+    fn __next__(
+        mut slf: PyRefMut<Self>,
+        py: Python,
+    ) -> PyResult<Option<Py<Match>>> {
+        let index = slf.index;
+        let pattern = slf.pattern.clone_ref(py);
+        let pattern = pattern.borrow(py);
+        if index >= pattern.matches.len() {
+            return Ok(None);
+        }
+        slf.index += 1;
+        Ok(Some(Py::new(py, match_from_tuple(pattern.matches[index]))?))
     }
 }
 
@@ -711,16 +1170,12 @@ impl Rules {
 #[pymethods]
 impl Rules {
     /// Scans in-memory data with these rules.
-    fn scan(&self, data: &[u8]) -> PyResult<Py<ScanResults>> {
+    fn scan(&self, py: Python, data: &[u8]) -> PyResult<Py<ScanResults>> {
         let mut scanner = yrx::Scanner::new(&self.inner.rules);
-        Python::with_gil(|py| {
-            scan_results_to_py(
-                py,
-                scanner
-                    .scan(data)
-                    .map_err(|err| ScanError::new_err(err.to_string()))?,
-            )
-        })
+        let results = py
+            .allow_threads(|| scanner.scan(data))
+            .map_err(|err| ScanError::new_err(err.to_string()))?;
+        scan_results_to_py(py, results)
     }
 
     /// Serializes the rules into a file-like object.
@@ -745,6 +1200,53 @@ impl Rules {
 
         Python::with_gil(|py| Py::new(py, Rules::new(rules)))
     }
+
+    /// Serializes the rules into a `bytes` object.
+    fn serialize<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyBytes>> {
+        let mut buf = Vec::new();
+        self.inner
+            .rules
+            .serialize_into(&mut buf)
+            .map_err(|err| SerializationError::new_err(err.to_string()))?;
+        Ok(PyBytes::new(py, &buf))
+    }
+
+    /// Equivalent to [`Rules::serialize`], invoked by the `bytes()` builtin.
+    ///
+    /// This lets compiled rules be embedded directly into other containers
+    /// (databases, message payloads, zip members) with `bytes(rules)`,
+    /// without having to call `serialize()` explicitly.
+    fn __bytes__<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyBytes>> {
+        self.serialize(py)
+    }
+
+    /// Deserializes rules from a `bytes` object produced by [`Rules::serialize`].
+    ///
+    /// # Raises
+    ///
+    /// [`SerializationError`] if `data` is not in the expected format, or
+    /// was produced by an incompatible version of YARA-X.
+    #[staticmethod]
+    fn deserialize(py: Python, data: &[u8]) -> PyResult<Py<Rules>> {
+        let rules = yrx::Rules::deserialize_from(data)
+            .map_err(|err| SerializationError::new_err(err.to_string()))?;
+
+        Py::new(py, Rules::new(rules))
+    }
+}
+
+/// Returns the item at `index` in `tuple`, supporting Python's negative
+/// indexing convention, or raises `IndexError` if `index` is out of bounds.
+fn tuple_get_item<'py>(
+    tuple: &Bound<'py, PyTuple>,
+    index: isize,
+) -> PyResult<Bound<'py, PyAny>> {
+    let len = tuple.len() as isize;
+    let i = if index < 0 { index + len } else { index };
+    if i < 0 || i >= len {
+        return Err(PyIndexError::new_err("index out of range"));
+    }
+    tuple.get_item(i as usize)
 }
 
 fn scan_results_to_py(
@@ -761,7 +1263,7 @@ fn scan_results_to_py(
 
     if outputs.len() > 0 {
         for (module, output) in outputs {
-            module_outputs.set_item(module, proto_to_json(py, output)?)?;
+            module_outputs.set_item(module, module_output_to_py(py, output)?)?;
         }
     }
 
@@ -821,25 +1323,10 @@ fn pattern_to_py(py: Python, pattern: yrx::Pattern) -> PyResult<Py<Pattern>> {
         py,
         Pattern {
             identifier: pattern.identifier().to_string(),
-            matches: PyTuple::new(
-                py,
-                pattern
-                    .matches()
-                    .map(|match_| match_to_py(py, match_))
-                    .collect::<Result<Vec<_>, _>>()?,
-            )?
-            .unbind(),
-        },
-    )
-}
-
-fn match_to_py(py: Python, match_: yrx::Match) -> PyResult<Py<Match>> {
-    Py::new(
-        py,
-        Match {
-            offset: match_.range().start,
-            length: match_.range().len(),
-            xor_key: match_.xor_key(),
+            matches: pattern
+                .matches()
+                .map(|m| (m.range().start, m.range().len(), m.xor_key()))
+                .collect(),
         },
     )
 }
@@ -865,6 +1352,9 @@ fn match_to_py(py: Python, match_: yrx::Match) -> PyResult<Py<Match>> {
 #[pyclass]
 struct JsonDecoder {
     fromtimestamp: Py<PyAny>,
+    // Handlers registered with `register` for encodings other than the
+    // built-in "base64" and "timestamp" ones.
+    handlers: std::collections::HashMap<String, PyObject>,
 }
 
 #[pymethods]
@@ -881,9 +1371,22 @@ impl JsonDecoder {
                     .unwrap()
                     .unbind()
             }),
+            handlers: std::collections::HashMap::new(),
         }
     }
 
+    /// Registers a handler for a custom `encoding`.
+    ///
+    /// Whenever the decoder finds an object of the form
+    /// `{"encoding": encoding, "value": ...}` that doesn't match one of the
+    /// built-in encodings ("base64", "timestamp"), it calls `handler` with
+    /// the `value` field and uses its return value in place of the object.
+    /// This allows extending the decoder for module output that uses
+    /// encodings YARA-X doesn't know about.
+    fn register(&mut self, encoding: &str, handler: PyObject) {
+        self.handlers.insert(encoding.to_string(), handler);
+    }
+
     fn __call__<'py>(
         &self,
         py: Python<'py>,
@@ -910,6 +1413,10 @@ impl JsonDecoder {
                 self.fromtimestamp
                     .call(py, (value,), Some(&kwargs))?
                     .into_bound_py_any(py)
+            } else if let Some(handler) =
+                self.handlers.get(encoding.to_cow()?.as_ref())
+            {
+                handler.call1(py, (value,))?.into_bound_py_any(py)
             } else {
                 Ok(dict.into_any())
             }
@@ -919,6 +1426,112 @@ impl JsonDecoder {
     }
 }
 
+// When set, module output is converted to Python objects by serializing it
+// to JSON and parsing it back, like `yara-x` did before `proto_to_py` was
+// added. This is kept around for debugging and for comparing both code
+// paths; `proto_to_py` is faster because it skips the JSON round trip.
+static USE_JSON_MODULE_OUTPUT: AtomicBool = AtomicBool::new(false);
+
+/// Selects how the output produced by YARA-X modules is converted to
+/// Python objects during a scan.
+///
+/// By default (`enabled=False`) module output is converted directly from
+/// its protobuf representation. Passing `True` makes it go through JSON
+/// instead, which is slower but kept as a fallback in case some module
+/// output doesn't convert correctly through the native path.
+#[pyfunction]
+fn use_json_module_output(enabled: bool) {
+    USE_JSON_MODULE_OUTPUT.store(enabled, Ordering::Relaxed);
+}
+
+fn module_output_to_py<'py>(
+    py: Python<'py>,
+    proto: &dyn MessageDyn,
+) -> PyResult<Bound<'py, PyAny>> {
+    if USE_JSON_MODULE_OUTPUT.load(Ordering::Relaxed) {
+        proto_to_json(py, proto)
+    } else {
+        proto_to_py(py, proto)
+    }
+}
+
+/// Converts a protobuf message produced by a YARA-X module into a Python
+/// dictionary, without going through a JSON intermediate representation.
+fn proto_to_py<'py>(
+    py: Python<'py>,
+    proto: &dyn MessageDyn,
+) -> PyResult<Bound<'py, PyAny>> {
+    let dict = PyDict::new(py);
+
+    for field in proto.descriptor_dyn().fields() {
+        match field.get_reflect(proto) {
+            ReflectFieldRef::Optional(value) => {
+                if let Some(value) = value.to_option() {
+                    dict.set_item(field.name(), reflect_value_to_py(py, value)?)?;
+                }
+            }
+            ReflectFieldRef::Repeated(values) => {
+                let items = values
+                    .into_iter()
+                    .map(|value| reflect_value_to_py(py, value))
+                    .collect::<PyResult<Vec<_>>>()?;
+                dict.set_item(field.name(), PyList::new(py, items)?)?;
+            }
+            ReflectFieldRef::Map(entries) => {
+                let items = PyDict::new(py);
+                for (key, value) in entries.into_iter() {
+                    items.set_item(
+                        reflect_value_to_py(py, key)?,
+                        reflect_value_to_py(py, value)?,
+                    )?;
+                }
+                dict.set_item(field.name(), items)?;
+            }
+        }
+    }
+
+    Ok(dict.into_any())
+}
+
+fn reflect_value_to_py<'py>(
+    py: Python<'py>,
+    value: ReflectValueRef<'_>,
+) -> PyResult<Bound<'py, PyAny>> {
+    match value {
+        ReflectValueRef::U32(v) => v.into_bound_py_any(py),
+        ReflectValueRef::U64(v) => v.into_bound_py_any(py),
+        ReflectValueRef::I32(v) => v.into_bound_py_any(py),
+        ReflectValueRef::I64(v) => v.into_bound_py_any(py),
+        ReflectValueRef::F32(v) => v.into_bound_py_any(py),
+        ReflectValueRef::F64(v) => v.into_bound_py_any(py),
+        ReflectValueRef::Bool(v) => v.into_bound_py_any(py),
+        ReflectValueRef::String(v) => v.into_bound_py_any(py),
+        ReflectValueRef::Bytes(v) => PyBytes::new(py, v).into_bound_py_any(py),
+        ReflectValueRef::Enum(descriptor, number) => {
+            match descriptor.value_by_number(number) {
+                Some(value) => value.name().into_bound_py_any(py),
+                None => number.into_bound_py_any(py),
+            }
+        }
+        ReflectValueRef::Message(msg) => proto_to_py(py, &*msg),
+    }
+}
+
+// When set (the default), `proto_to_json` parses its JSON with the
+// Rust-side `jiter`-based reader, and only falls back to Python's
+// `json.loads` for JSON that reader can't handle. When cleared, for
+// instance for debugging, `json.loads` is always used.
+static USE_JITER: AtomicBool = AtomicBool::new(true);
+
+/// Selects whether the JSON fallback path (used when
+/// [`use_json_module_output`] is enabled) parses module output with a
+/// Rust-side streaming reader (the default, `enabled=True`) or by calling
+/// Python's `json.loads` with an `object_hook` (`enabled=False`).
+#[pyfunction]
+fn use_jiter(enabled: bool) {
+    USE_JITER.store(enabled, Ordering::Relaxed);
+}
+
 fn proto_to_json<'py>(
     py: Python<'py>,
     proto: &dyn MessageDyn,
@@ -932,6 +1545,14 @@ fn proto_to_json<'py>(
         .serialize(proto)
         .expect("unable to serialize JSON produced by module");
 
+    if USE_JITER.load(Ordering::Relaxed) {
+        if let Ok(value) = jiter_to_py(py, &module_output_json) {
+            return Ok(value);
+        }
+        // Fall through to `json.loads` below if the fast reader couldn't
+        // make sense of the output for some reason.
+    }
+
     let json = PyModule::import(py, "json")?;
     let json_loads = json.getattr("loads")?;
 
@@ -950,6 +1571,99 @@ fn proto_to_json<'py>(
     json_loads.call((module_output_json,), Some(&kwargs))
 }
 
+/// Parses `data` with [`jiter`] and converts the result directly into
+/// Python objects, without allocating an intermediate Python `str`/`bytes`
+/// buffer or invoking a Python callback per object like the `json.loads`
+/// `object_hook` path does.
+///
+/// Tagged objects of the form `{"encoding": ..., "value": ...}` are decoded
+/// inline as they're encountered, applying the same `base64`/`timestamp`
+/// semantics as [`JsonDecoder`].
+fn jiter_to_py<'py>(
+    py: Python<'py>,
+    data: &[u8],
+) -> Result<Bound<'py, PyAny>, jiter::JiterError> {
+    let mut jiter = jiter::Jiter::new(data);
+    let value = jiter.next_value()?;
+    jiter.finish()?;
+    Ok(jiter_value_to_py(py, &value))
+}
+
+fn jiter_value_to_py<'py>(
+    py: Python<'py>,
+    value: &jiter::JsonValue,
+) -> Bound<'py, PyAny> {
+    match value {
+        jiter::JsonValue::Null => py.None().into_bound(py),
+        jiter::JsonValue::Bool(b) => b.into_bound_py_any(py).unwrap(),
+        jiter::JsonValue::Int(i) => i.into_bound_py_any(py).unwrap(),
+        jiter::JsonValue::Float(f) => f.into_bound_py_any(py).unwrap(),
+        jiter::JsonValue::Str(s) => s.as_ref().into_bound_py_any(py).unwrap(),
+        jiter::JsonValue::Array(items) => {
+            let items = items
+                .iter()
+                .map(|item| jiter_value_to_py(py, item))
+                .collect::<Vec<_>>();
+            PyList::new(py, items).unwrap().into_any()
+        }
+        jiter::JsonValue::Object(entries) => {
+            // `{"encoding": ..., "value": ...}` is how raw bytes and
+            // timestamps are represented in the JSON produced by
+            // `yara_x_proto_json::Serializer`, since they're not directly
+            // representable in JSON. See `JsonDecoder`'s documentation.
+            if let (Some(jiter::JsonValue::Str(encoding)), Some(value)) =
+                (entries.get("encoding"), entries.get("value"))
+            {
+                if let Some(decoded) =
+                    jiter_decode_tagged(py, encoding.as_ref(), value)
+                {
+                    return decoded;
+                }
+            }
+
+            let dict = PyDict::new(py);
+            for (key, value) in entries.iter() {
+                dict.set_item(key.as_ref(), jiter_value_to_py(py, value))
+                    .unwrap();
+            }
+            dict.into_any()
+        }
+    }
+}
+
+fn jiter_decode_tagged<'py>(
+    py: Python<'py>,
+    encoding: &str,
+    value: &jiter::JsonValue,
+) -> Option<Bound<'py, PyAny>> {
+    if encoding == "base64" {
+        let jiter::JsonValue::Str(value) = value else {
+            return None;
+        };
+        let bytes = BASE64_STANDARD.decode(value.as_bytes()).ok()?;
+        Some(PyBytes::new(py, &bytes).into_any())
+    } else if encoding == "timestamp" {
+        let timestamp = match value {
+            jiter::JsonValue::Int(i) => *i as f64,
+            jiter::JsonValue::Float(f) => *f,
+            jiter::JsonValue::Str(s) => s.parse().ok()?,
+            _ => return None,
+        };
+        let datetime = PyModule::import(py, "datetime").ok()?;
+        let kwargs = PyDict::new(py);
+        kwargs.set_item("tz", PyTzInfo::utc(py).ok()?).ok()?;
+        datetime
+            .getattr("datetime")
+            .ok()?
+            .getattr("fromtimestamp")
+            .ok()?
+            .call((timestamp,), Some(&kwargs))
+            .ok()
+    } else {
+        None
+    }
+}
+
 create_exception!(
     yara_x,
     CompileError,
@@ -971,6 +1685,15 @@ create_exception!(
     "Exception raised when scanning fails"
 );
 
+create_exception!(
+    yara_x,
+    SerializationError,
+    PyException,
+    "Exception raised when rules can't be serialized or deserialized, \
+    for example because the data being deserialized is not in the expected \
+    format, or was produced by an incompatible version of YARA-X"
+);
+
 fn map_scan_err(err: yrx::errors::ScanError) -> PyErr {
     match err {
         yrx::errors::ScanError::Timeout => TimeoutError::new_err("timeout"),
@@ -991,15 +1714,34 @@ fn yara_x(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add("CompileError", m.py().get_type::<CompileError>())?;
     m.add("TimeoutError", m.py().get_type::<TimeoutError>())?;
     m.add("ScanError", m.py().get_type::<ScanError>())?;
+    m.add("SerializationError", m.py().get_type::<SerializationError>())?;
     m.add_function(wrap_pyfunction!(compile, m)?)?;
+    m.add_function(wrap_pyfunction!(use_json_module_output, m)?)?;
+    m.add_function(wrap_pyfunction!(use_jiter, m)?)?;
     m.add_class::<Rules>()?;
     m.add_class::<Scanner>()?;
     m.add_class::<Compiler>()?;
     m.add_class::<Rule>()?;
     m.add_class::<Pattern>()?;
     m.add_class::<Match>()?;
+    m.add_class::<MatchIterator>()?;
     m.add_class::<Formatter>()?;
     m.add_class::<Module>()?;
     m.add_class::<JsonDecoder>()?;
+
+    let modules = PyModule::new(m.py(), "modules")?;
+    modules.add_function(wrap_pyfunction!(macho, &modules)?)?;
+    modules.add_function(wrap_pyfunction!(elf, &modules)?)?;
+    modules.add_function(wrap_pyfunction!(pe, &modules)?)?;
+    modules.add_function(wrap_pyfunction!(dotnet, &modules)?)?;
+    modules.add_function(wrap_pyfunction!(lnk, &modules)?)?;
+    // Make `import yara_x.modules` work, not just `yara_x.modules.pe(...)`
+    // via the parent module's attribute.
+    m.py()
+        .import("sys")?
+        .getattr("modules")?
+        .set_item("yara_x.modules", &modules)?;
+    m.add_submodule(&modules)?;
+
     Ok(())
 }
@@ -0,0 +1,324 @@
+//! Generates `re::instr`'s opcode plumbing from a single declarative table.
+//!
+//! `src/re/instructions.in` lists every VM instruction once: its name,
+//! opcode byte and operand layout. This script turns that table into
+//! `$OUT_DIR/instr_generated.rs`, which `src/re/instr.rs` pulls in with
+//! `include!`. Generating the `Instr` enum, `decode_instr` and the `emit`
+//! encoders from the same table is what keeps the regexp VM's decoder and
+//! the compiler's encoder byte-compatible with each other.
+
+use std::env;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+
+/// One row of `instructions.in`.
+struct InstrDef {
+    name: String,
+    opcode: u8,
+    operands: Vec<Operand>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Operand {
+    U8,
+    U16,
+    I32,
+    Bitmap,
+    Ranges,
+}
+
+impl Operand {
+    fn parse(s: &str) -> Self {
+        match s {
+            "u8" => Operand::U8,
+            "u16" => Operand::U16,
+            "i32" => Operand::I32,
+            "bitmap" => Operand::Bitmap,
+            "ranges" => Operand::Ranges,
+            other => panic!("unknown operand kind `{other}` in instructions.in"),
+        }
+    }
+
+    /// The type used for this operand in the generated `Instr` variant.
+    fn rust_type(&self) -> &'static str {
+        match self {
+            Operand::U8 => "u8",
+            Operand::U16 => "u16",
+            Operand::I32 => "i32",
+            Operand::Bitmap => "ClassBitmap",
+            Operand::Ranges => "ClassRanges",
+        }
+    }
+}
+
+fn parse_table(src: &str) -> Vec<InstrDef> {
+    let mut defs = Vec::new();
+    for line in src.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let mut cols = line.split_whitespace();
+        let name = cols.next().expect("missing instruction name").to_string();
+        let opcode = cols.next().expect("missing opcode");
+        let opcode = u8::from_str_radix(
+            opcode.trim_start_matches("0x"),
+            16,
+        )
+        .expect("opcode must be a hex byte");
+        let operands = cols.next().expect("missing operand layout");
+        let operands = if operands == "-" {
+            Vec::new()
+        } else {
+            operands.split(',').map(Operand::parse).collect()
+        };
+        defs.push(InstrDef { name, opcode, operands });
+    }
+    defs
+}
+
+/// Generates the `Instr` enum variants.
+fn gen_enum(defs: &[InstrDef], out: &mut String) {
+    writeln!(out, "/// A single VM instruction, as produced by `decode_instr`.").unwrap();
+    writeln!(out, "#[derive(Debug, Clone, PartialEq)]").unwrap();
+    writeln!(out, "pub(crate) enum Instr {{").unwrap();
+    for def in defs {
+        if def.operands.is_empty() {
+            writeln!(out, "    {},", def.name).unwrap();
+        } else {
+            let types: Vec<_> =
+                def.operands.iter().map(Operand::rust_type).collect();
+            writeln!(out, "    {}({}),", def.name, types.join(", ")).unwrap();
+        }
+    }
+    writeln!(out, "}}").unwrap();
+}
+
+/// Generates `decode_instr`, returning the decoded instruction and the
+/// total size in bytes (opcode + operands) of the instruction it was
+/// decoded from.
+fn gen_decoder(defs: &[InstrDef], out: &mut String) {
+    writeln!(
+        out,
+        "/// Decodes the instruction at the start of `code`, returning the \
+         decoded\n/// [`Instr`] and the number of bytes it occupies."
+    )
+    .unwrap();
+    writeln!(
+        out,
+        "pub(crate) fn decode_instr(code: &[u8]) -> (Instr, usize) {{"
+    )
+    .unwrap();
+    writeln!(out, "    match code[0] {{").unwrap();
+    for def in defs {
+        writeln!(out, "        0x{:02x} => {{", def.opcode).unwrap();
+        writeln!(out, "            let mut size = 1;").unwrap();
+        let mut binds = Vec::new();
+        for (i, operand) in def.operands.iter().enumerate() {
+            let bind = format!("operand_{i}");
+            match operand {
+                Operand::U8 => {
+                    writeln!(
+                        out,
+                        "            let {bind} = code[size];"
+                    )
+                    .unwrap();
+                    writeln!(out, "            size += 1;").unwrap();
+                }
+                Operand::U16 => {
+                    writeln!(out,
+                        "            let {bind} = u16::from_le_bytes(code[size..size + 2].try_into().unwrap());"
+                    ).unwrap();
+                    writeln!(out, "            size += 2;").unwrap();
+                }
+                Operand::I32 => {
+                    writeln!(out,
+                        "            let {bind} = i32::from_le_bytes(code[size..size + 4].try_into().unwrap());"
+                    ).unwrap();
+                    writeln!(out, "            size += 4;").unwrap();
+                }
+                Operand::Bitmap => {
+                    writeln!(out,
+                        "            let {bind} = ClassBitmap::from_bytes(&code[size..size + 32]);"
+                    ).unwrap();
+                    writeln!(out, "            size += 32;").unwrap();
+                }
+                Operand::Ranges => {
+                    writeln!(out,
+                        "            let num_ranges = u16::from_le_bytes(code[size..size + 2].try_into().unwrap()) as usize;"
+                    ).unwrap();
+                    writeln!(out, "            size += 2;").unwrap();
+                    writeln!(out,
+                        "            let {bind} = ClassRanges::from_bytes(&code[size..size + num_ranges * 2]);"
+                    ).unwrap();
+                    writeln!(out, "            size += num_ranges * 2;").unwrap();
+                }
+            }
+            binds.push(bind);
+        }
+        if binds.is_empty() {
+            writeln!(out, "            (Instr::{}, size)", def.name).unwrap();
+        } else {
+            writeln!(
+                out,
+                "            (Instr::{}({}), size)",
+                def.name,
+                binds.join(", ")
+            )
+            .unwrap();
+        }
+        writeln!(out, "        }}").unwrap();
+    }
+    writeln!(out, "        opcode => unreachable!(\"unknown opcode {{opcode:#04x}}\"),").unwrap();
+    writeln!(out, "    }}").unwrap();
+    writeln!(out, "}}").unwrap();
+}
+
+/// Generates the `emit` module used by `re::compiler::Compiler` to append
+/// instructions to the code buffer being built.
+fn gen_encoder(defs: &[InstrDef], out: &mut String) {
+    writeln!(out, "/// Encoders used by the compiler to emit VM code.").unwrap();
+    writeln!(out, "pub(crate) mod emit {{").unwrap();
+    writeln!(out, "    use super::{{ClassBitmap, ClassRanges}};").unwrap();
+    for def in defs {
+        let fn_name = to_snake_case(&def.name);
+        let params: Vec<_> = def
+            .operands
+            .iter()
+            .enumerate()
+            .map(|(i, operand)| format!("operand_{i}: {}", operand_param_type(*operand)))
+            .collect();
+        writeln!(
+            out,
+            "    pub(crate) fn {}(code: &mut Vec<u8>{}) {{",
+            fn_name,
+            params.iter().fold(String::new(), |mut acc, p| {
+                acc.push_str(", ");
+                acc.push_str(p);
+                acc
+            })
+        )
+        .unwrap();
+        writeln!(out, "        code.push(0x{:02x});", def.opcode).unwrap();
+        for (i, operand) in def.operands.iter().enumerate() {
+            let bind = format!("operand_{i}");
+            match operand {
+                Operand::U8 => {
+                    writeln!(out, "        code.push({bind});").unwrap();
+                }
+                Operand::U16 => {
+                    writeln!(
+                        out,
+                        "        code.extend_from_slice(&{bind}.to_le_bytes());"
+                    )
+                    .unwrap();
+                }
+                Operand::I32 => {
+                    writeln!(
+                        out,
+                        "        code.extend_from_slice(&{bind}.to_le_bytes());"
+                    )
+                    .unwrap();
+                }
+                Operand::Bitmap => {
+                    writeln!(
+                        out,
+                        "        code.extend_from_slice(&{bind}.to_bytes());"
+                    )
+                    .unwrap();
+                }
+                Operand::Ranges => {
+                    writeln!(out,
+                        "        code.extend_from_slice(&(({bind}.to_bytes().len() / 2) as u16).to_le_bytes());"
+                    ).unwrap();
+                    writeln!(
+                        out,
+                        "        code.extend_from_slice(&{bind}.to_bytes());"
+                    )
+                    .unwrap();
+                }
+            }
+        }
+        writeln!(out, "    }}").unwrap();
+    }
+    writeln!(out, "}}").unwrap();
+}
+
+fn operand_param_type(operand: Operand) -> &'static str {
+    match operand {
+        Operand::U8 => "u8",
+        Operand::U16 => "u16",
+        Operand::I32 => "i32",
+        Operand::Bitmap => "&ClassBitmap",
+        Operand::Ranges => "&ClassRanges",
+    }
+}
+
+/// Generates `disassemble`, a debug helper that prints compiled VM code in
+/// human-readable form, one instruction per line.
+fn gen_disassembler(out: &mut String) {
+    writeln!(
+        out,
+        "/// Returns a human-readable listing of the VM code in `code`, one \
+         instruction\n/// per line, prefixed with its offset. Intended for \
+         debugging compiled patterns."
+    )
+    .unwrap();
+    writeln!(
+        out,
+        "pub(crate) fn disassemble(code: &[u8]) -> String {{"
+    )
+    .unwrap();
+    writeln!(out, "    use std::fmt::Write;").unwrap();
+    writeln!(out, "    let mut result = String::new();").unwrap();
+    writeln!(out, "    let mut ip = 0;").unwrap();
+    writeln!(out, "    while ip < code.len() {{").unwrap();
+    writeln!(out, "        let (instr, size) = decode_instr(&code[ip..]);").unwrap();
+    writeln!(
+        out,
+        "        let _ = writeln!(result, \"{{ip:06}}: {{instr:?}}\");"
+    )
+    .unwrap();
+    writeln!(out, "        ip += size;").unwrap();
+    writeln!(out, "    }}").unwrap();
+    writeln!(out, "    result").unwrap();
+    writeln!(out, "}}").unwrap();
+}
+
+fn to_snake_case(name: &str) -> String {
+    let mut result = String::new();
+    for (i, c) in name.char_indices() {
+        if c.is_uppercase() {
+            if i != 0 {
+                result.push('_');
+            }
+            result.extend(c.to_lowercase());
+        } else {
+            result.push(c);
+        }
+    }
+    result
+}
+
+fn main() {
+    let table_path = Path::new("src/re/instructions.in");
+    println!("cargo:rerun-if-changed={}", table_path.display());
+
+    let src = fs::read_to_string(table_path)
+        .expect("failed to read src/re/instructions.in");
+    let defs = parse_table(&src);
+
+    let mut out = String::new();
+    gen_enum(&defs, &mut out);
+    out.push('\n');
+    gen_decoder(&defs, &mut out);
+    out.push('\n');
+    gen_encoder(&defs, &mut out);
+    out.push('\n');
+    gen_disassembler(&mut out);
+
+    let out_dir = env::var("OUT_DIR").expect("OUT_DIR not set");
+    let dest = Path::new(&out_dir).join("instr_generated.rs");
+    fs::write(&dest, out).expect("failed to write instr_generated.rs");
+}
@@ -0,0 +1,304 @@
+//! VM instruction set used by [`crate::re::pikevm::PikeVM`].
+//!
+//! The opcode byte, the `Instr` enum, `decode_instr` and the `emit` encoders
+//! used by [`crate::re::compiler::Compiler`] are generated from the single
+//! declarative table in `instructions.in` by `build.rs`, so the decoder the
+//! VM uses and the encoder the compiler uses can never go out of sync. Only
+//! the operand types (`ClassBitmap`, `ClassRanges`) and the epsilon-closure
+//! support code below are hand-written.
+
+/// A 256-bit bitmap used by `Instr::ClassBitmap` to represent a character
+/// class as a "does this byte belong to the class" membership test.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct ClassBitmap([u8; 32]);
+
+impl ClassBitmap {
+    pub(crate) fn new() -> Self {
+        Self([0; 32])
+    }
+
+    pub(crate) fn set(&mut self, byte: u8) {
+        self.0[(byte / 8) as usize] |= 1 << (byte % 8);
+    }
+
+    pub(crate) fn contains(&self, byte: u8) -> bool {
+        self.0[(byte / 8) as usize] & (1 << (byte % 8)) != 0
+    }
+
+    pub(crate) fn to_bytes(self) -> [u8; 32] {
+        self.0
+    }
+
+    pub(crate) fn from_bytes(bytes: &[u8]) -> Self {
+        let mut bitmap = [0; 32];
+        bitmap.copy_from_slice(bytes);
+        Self(bitmap)
+    }
+}
+
+/// A list of inclusive `(start, end)` byte ranges used by
+/// `Instr::ClassRanges` to represent sparse character classes more
+/// compactly than a full [`ClassBitmap`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ClassRanges(Vec<(u8, u8)>);
+
+impl ClassRanges {
+    pub(crate) fn new(ranges: Vec<(u8, u8)>) -> Self {
+        Self(ranges)
+    }
+
+    pub(crate) fn contains(&self, byte: u8) -> bool {
+        self.0.iter().any(|(start, end)| (*start..=*end).contains(&byte))
+    }
+
+    pub(crate) fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.0.len() * 2);
+        for (start, end) in &self.0 {
+            bytes.push(*start);
+            bytes.push(*end);
+        }
+        bytes
+    }
+
+    pub(crate) fn from_bytes(bytes: &[u8]) -> Self {
+        Self(bytes.chunks_exact(2).map(|r| (r[0], r[1])).collect())
+    }
+}
+
+include!(concat!(env!("OUT_DIR"), "/instr_generated.rs"));
+
+use std::rc::Rc;
+
+/// A location within the VM code produced by
+/// [`crate::re::compiler::Compiler`].
+///
+/// `PikeVM::try_match` is generic over `CodeLoc` so that the same matching
+/// loop can be used both for forward code (where `location()` grows as the
+/// VM advances) and for the backward code emitted for patterns that need to
+/// match right-to-left.
+pub(crate) trait CodeLoc: Copy {
+    fn from(location: usize) -> Self;
+    fn location(&self) -> usize;
+}
+
+/// The simplest [`CodeLoc`] implementation: a plain forward offset into the
+/// code buffer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct ForwardCodeLoc(usize);
+
+impl CodeLoc for ForwardCodeLoc {
+    fn from(location: usize) -> Self {
+        Self(location)
+    }
+
+    fn location(&self) -> usize {
+        self.0
+    }
+}
+
+/// A thread's capture slots, indexed by the slot number used in `Save(slot)`
+/// instructions. A slot is `None` until the thread's path through the code
+/// executes the `Save` that writes it.
+///
+/// Threads share their capture vector via `Rc` when spawned from the same
+/// parent (e.g. by a `Split`), so forking a thread is O(1). Only the thread
+/// that actually executes a `Save` pays for a copy, via [`Rc::make_mut`],
+/// and only of the one vector it's about to mutate — not of every thread
+/// sharing it.
+pub(crate) type Captures = Rc<Vec<Option<usize>>>;
+
+/// A read-only view of a matched thread's capture slots, as returned by
+/// [`crate::re::pikevm::PikeVM::try_match`].
+pub(crate) type CapturesSlice<'a> = &'a [Option<usize>];
+
+/// A single active thread in the Pike VM: an instruction pointer plus the
+/// capture slots accumulated by the path that reached it.
+///
+/// Threads are deduplicated by `pc` alone (never by `captures`), which is
+/// what gives the VM its "one thread per instruction" invariant and its
+/// O(threads × input) time bound. Letting captures vary would allow the
+/// same `pc` to appear multiple times and defeat that bound.
+pub(crate) struct Thread {
+    pub(crate) pc: usize,
+    pub(crate) captures: Captures,
+}
+
+/// A sparse set of [`Thread`]s keyed by `pc`, sized to the VM code length.
+///
+/// This is the classic sparse-set trick: `dense` holds the threads in
+/// insertion order, `sparse[pc]` holds `dense`'s index for that `pc`. A
+/// `pc` is a member only when `sparse[pc]` is in bounds for the *current*
+/// `dense` *and* points back at a `dense` entry with that same `pc` — a
+/// stale `sparse` entry left over from a previous generation just fails
+/// that check, so `clear` is a single `dense.clear()` rather than a pass
+/// over every previously-active `pc`.
+///
+/// Keeping `dense` in insertion order (never reordering or swap-removing)
+/// is what preserves thread priority: the VM always wants the first thread
+/// to reach `Match` to win (leftmost-first, greedy-first), and that only
+/// holds if iterating `dense` visits threads in the order they were added.
+pub(crate) struct ThreadSet {
+    dense: Vec<Thread>,
+    sparse: Vec<u32>,
+}
+
+impl ThreadSet {
+    pub(crate) fn new(code_len: usize) -> Self {
+        Self { dense: Vec::new(), sparse: vec![0; code_len] }
+    }
+
+    /// Resizes the set for VM code of a different length, if needed. A
+    /// no-op (and therefore O(1)) when the code length hasn't changed,
+    /// which is the common case of matching the same pattern repeatedly.
+    pub(crate) fn resize(&mut self, code_len: usize) {
+        if self.sparse.len() != code_len {
+            self.sparse = vec![0; code_len];
+        }
+    }
+
+    /// Empties the set in O(1): no write to `sparse` is needed, see the
+    /// type-level docs.
+    pub(crate) fn clear(&mut self) {
+        self.dense.clear();
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.dense.is_empty()
+    }
+
+    pub(crate) fn contains(&self, pc: usize) -> bool {
+        match self.sparse.get(pc) {
+            Some(&index) => {
+                let index = index as usize;
+                index < self.dense.len() && self.dense[index].pc == pc
+            }
+            None => false,
+        }
+    }
+
+    /// Inserts `thread` at the end of `dense`. The caller must ensure
+    /// `!self.contains(thread.pc)` first; `epsilon_closure` does, as part
+    /// of the same check it needs for the epsilon-transition visited set.
+    pub(crate) fn insert(&mut self, thread: Thread) {
+        self.sparse[thread.pc] = self.dense.len() as u32;
+        self.dense.push(thread);
+    }
+
+    pub(crate) fn iter(&self) -> std::slice::Iter<'_, Thread> {
+        self.dense.iter()
+    }
+}
+
+/// Reusable scratch space for [`epsilon_closure`], so that computing the
+/// epsilon closure at every step of [`crate::re::pikevm::PikeVM::try_match`]
+/// doesn't allocate a new stack on every call.
+///
+/// `epsilon_closure` also needs to avoid revisiting the `Jump`/`Split`/
+/// `Save` instructions it walks through on the way to a thread (those never
+/// end up in the resulting [`ThreadSet`], so that set's own membership
+/// check doesn't cover them) — both to avoid infinite loops on epsilon
+/// cycles and exponential blowup on diamond-shaped splits. `visited_gen`
+/// tracks that, keyed by `pc`, but as a generation stamp rather than a
+/// `bool`: starting a new step just bumps `current_gen`, an O(1) operation,
+/// instead of rewriting every slot back to `false`.
+pub(crate) struct EpsilonClosureState {
+    stack: Vec<(usize, Captures)>,
+    visited_gen: Vec<u32>,
+    current_gen: u32,
+}
+
+impl EpsilonClosureState {
+    pub(crate) fn new() -> Self {
+        Self { stack: Vec::new(), visited_gen: Vec::new(), current_gen: 0 }
+    }
+
+    /// Starts a new step: every `pc` becomes eligible for `threads` again.
+    /// `code_len` must match the VM code being matched; the backing vector
+    /// is only reallocated when it changes.
+    pub(crate) fn start_step(&mut self, code_len: usize) {
+        if self.visited_gen.len() != code_len {
+            self.visited_gen = vec![0; code_len];
+            self.current_gen = 0;
+        }
+        self.current_gen += 1;
+    }
+
+    /// Returns whether `pc` was already visited during the current step,
+    /// marking it visited as a side effect.
+    fn visit(&mut self, pc: usize) -> bool {
+        if self.visited_gen[pc] == self.current_gen {
+            true
+        } else {
+            self.visited_gen[pc] = self.current_gen;
+            false
+        }
+    }
+}
+
+/// Follows every `Jump`, `Split` and `Save` instruction reachable from
+/// `start` without consuming any input, inserting a [`Thread`] into
+/// `threads` for every distinct byte-consuming instruction (or
+/// `Match`/`Eoi`) found along the way.
+///
+/// `captures` is the capture vector of the thread this closure is being
+/// computed for (or a fresh, all-`None` vector when seeding the initial
+/// thread list). Each `Save(slot)` instruction encountered records
+/// `current_pos` into `slot` of its own copy-on-write branch of `captures`
+/// before continuing, so that threads which diverge at a `Split` downstream
+/// of a `Save` don't see each other's writes.
+///
+/// `closure_state` is scratch space reused across calls; its visited
+/// generation is *not* bumped here — see [`EpsilonClosureState::start_step`].
+/// `threads` is *not* cleared here either, since `try_match` calls this
+/// once per surviving thread and accumulates all of them into the same
+/// next-step set. `next_byte` and `prev_byte` are not consumed here, they
+/// are threaded through so that look-around assertions (not yet part of
+/// the opcode table) can inspect the surrounding bytes once added.
+pub(crate) fn epsilon_closure(
+    code: &[u8],
+    start: impl CodeLoc,
+    current_pos: usize,
+    _next_byte: Option<&u8>,
+    _prev_byte: Option<&u8>,
+    captures: &Captures,
+    closure_state: &mut EpsilonClosureState,
+    threads: &mut ThreadSet,
+) {
+    closure_state.stack.clear();
+    closure_state.stack.push((start.location(), captures.clone()));
+
+    while let Some((ip, captures)) = closure_state.stack.pop() {
+        if closure_state.visit(ip) {
+            continue;
+        }
+
+        let (instr, size) = decode_instr(&code[ip..]);
+
+        match instr {
+            Instr::Jump(offset) => {
+                closure_state
+                    .stack
+                    .push(((ip as i64 + offset as i64) as usize, captures));
+            }
+            Instr::Split(a, b) => {
+                closure_state.stack.push((
+                    (ip as i64 + b as i64) as usize,
+                    captures.clone(),
+                ));
+                closure_state
+                    .stack
+                    .push(((ip as i64 + a as i64) as usize, captures));
+            }
+            Instr::Save(slot) => {
+                let mut captures = captures;
+                Rc::make_mut(&mut captures)[slot as usize] = Some(current_pos);
+                closure_state.stack.push((ip + size, captures));
+            }
+            _ => {
+                if !threads.contains(ip) {
+                    threads.insert(Thread { pc: ip, captures });
+                }
+            }
+        }
+    }
+}
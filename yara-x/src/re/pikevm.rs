@@ -1,37 +1,52 @@
 use std::mem;
+use std::rc::Rc;
 
 use crate::re::instr::{
-    decode_instr, epsilon_closure, CodeLoc, EpsilonClosureState, Instr,
+    decode_instr, epsilon_closure, Captures, CapturesSlice, CodeLoc,
+    EpsilonClosureState, Instr, ThreadSet,
 };
 
 /// Represents a [Pike's VM](https://swtch.com/~rsc/regexp/regexp2.html) that
 /// executes VM code produced by the [compiler][`crate::re::compiler::Compiler`].
 pub(crate) struct PikeVM {
-    /// The list of currently active threads. Each item in this list is a
-    /// position within the VM code, pointing to some VM instruction. Each item
-    /// in the list is unique, the VM guarantees that there aren't two active
-    /// threads at the same VM instruction.
-    threads: Vec<usize>,
-    /// The list of threads that will become the active threads when the next
+    /// The set of currently active threads, keyed by the VM instruction
+    /// (`pc`) each one is sitting at. Backed by a sparse set so membership
+    /// checks and inserts are O(1) and clearing it between input bytes is
+    /// O(1) too — the VM guarantees that there aren't two active threads at
+    /// the same VM instruction, which is exactly what the set enforces.
+    threads: ThreadSet,
+    /// The set of threads that will become the active threads when the next
     /// byte is read from the input.
-    next_threads: Vec<usize>,
+    next_threads: ThreadSet,
     cache: EpsilonClosureState,
+    /// The capture slots of the thread that last reached `Instr::Match`.
+    /// Populated by `try_match`, which returns a view into it.
+    match_captures: Captures,
 }
 
 impl PikeVM {
     /// Creates a new [`PikeVM`].
     pub fn new() -> Self {
         Self {
-            threads: Vec::new(),
-            next_threads: Vec::new(),
+            threads: ThreadSet::new(0),
+            next_threads: ThreadSet::new(0),
             cache: EpsilonClosureState::new(),
+            match_captures: Rc::new(Vec::new()),
         }
     }
 
     /// Executes VM code starting at the `start` location and returns the
-    /// number of bytes from `fwd_input` that matched. The number of bytes
-    /// returned can be zero if the VM matches a zero-length string. Returns
-    /// `None` if the data read from input don't match the regexp.
+    /// number of bytes from `fwd_input` that matched, together with the
+    /// capture slots recorded along the way, indexed by the slot number
+    /// used in the pattern's `Save(slot)` instructions. Returns `None` if
+    /// the data read from input don't match the regexp.
+    ///
+    /// `num_captures` is the number of capture slots the pattern being
+    /// matched defines; every thread starts with that many `None` slots.
+    ///
+    /// The number of bytes returned can be zero if the VM matches a
+    /// zero-length string. A slot that the winning thread never visited
+    /// (e.g. a capturing group on a branch that wasn't taken) stays `None`.
     ///
     /// `bck_input` is an iterator that returns the bytes that were before
     /// the stating point of `fwd_input`, in reverse order. For instance,
@@ -41,7 +56,7 @@ impl PikeVM {
     ///
     /// ```text
     ///       a  b  c  e  f   g   h   i
-    ///                   |  
+    ///                   |
     ///      <- bck_input | fwd_input ->
     /// ```
     ///
@@ -53,9 +68,10 @@ impl PikeVM {
         &mut self,
         code: &[u8],
         start: C,
+        num_captures: usize,
         mut fwd_input: F,
         mut bck_input: B,
-    ) -> Option<usize>
+    ) -> Option<(usize, CapturesSlice<'_>)>
     where
         C: CodeLoc,
         F: Iterator<Item = &'a u8>,
@@ -66,11 +82,20 @@ impl PikeVM {
         let mut current_pos = 0;
         let mut byte = fwd_input.next();
 
+        let no_captures: Captures = Rc::new(vec![None; num_captures]);
+
+        self.threads.resize(code.len());
+        self.next_threads.resize(code.len());
+        self.threads.clear();
+        self.cache.start_step(code.len());
+
         epsilon_closure(
             code,
             start,
+            current_pos,
             byte,
             bck_input.next(),
+            &no_captures,
             &mut self.cache,
             &mut self.threads,
         );
@@ -78,8 +103,10 @@ impl PikeVM {
         while !self.threads.is_empty() {
             let next_byte = fwd_input.next();
 
-            for ip in self.threads.iter() {
-                let (instr, size) = decode_instr(&code[*ip..]);
+            self.cache.start_step(code.len());
+
+            for thread in self.threads.iter() {
+                let (instr, size) = decode_instr(&code[thread.pc..]);
 
                 let is_match = match instr {
                     Instr::AnyByte => byte.is_some(),
@@ -97,6 +124,7 @@ impl PikeVM {
                     }
                     Instr::Match => {
                         matched_bytes = Some(current_pos);
+                        self.match_captures = thread.captures.clone();
                         // if non-greedy break
                         break;
                     }
@@ -110,9 +138,11 @@ impl PikeVM {
                 if is_match {
                     epsilon_closure(
                         code,
-                        C::from(*ip + size),
+                        C::from(thread.pc + size),
+                        current_pos + step,
                         next_byte,
                         byte,
+                        &thread.captures,
                         &mut self.cache,
                         &mut self.next_threads,
                     );
@@ -125,6 +155,6 @@ impl PikeVM {
             self.next_threads.clear();
         }
 
-        matched_bytes
+        matched_bytes.map(|len| (len, self.match_captures.as_slice()))
     }
-}
\ No newline at end of file
+}